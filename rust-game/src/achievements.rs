@@ -0,0 +1,248 @@
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, BTreeSet};
+use crate::simulation::{GameEvent, RunSummary};
+
+/// What an achievement measures. Progress counts towards [`Condition::target`].
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Condition {
+    /// Complete this many runs.
+    RunsCompleted(u32),
+    /// Complete a run at this net WPM.
+    Wpm(u32),
+    /// Complete `runs` runs at `accuracy` percent or better.
+    AccurateRuns { accuracy: i32, runs: u32 },
+    /// Destroy this many characters in total, inside words or not.
+    CharactersTyped(u32),
+    /// Reach this combo in one run.
+    Combo(u32),
+    /// Complete the level at this 1-based position in the pack.
+    LevelCleared(u32),
+    /// Finish this many words in total.
+    WordsTyped(u32),
+    /// Play on this many consecutive days.
+    StreakDays(u32),
+}
+
+impl Condition {
+    pub fn target(&self) -> u32 {
+        match *self {
+            Self::RunsCompleted(n)
+            | Self::Wpm(n)
+            | Self::CharactersTyped(n)
+            | Self::Combo(n)
+            | Self::LevelCleared(n)
+            | Self::WordsTyped(n)
+            | Self::StreakDays(n) => n,
+            Self::AccurateRuns { runs, .. } => runs,
+        }
+    }
+}
+
+/// How an observation moves an achievement's progress.
+enum Update {
+    Add(u32),
+    Max(u32),
+    Set(u32),
+}
+
+pub struct Achievement {
+    /// Matches the id used by the web app's `achievements.ts`.
+    pub id: &'static str,
+    pub name: &'static str,
+    pub description: &'static str,
+    pub condition: Condition,
+}
+
+pub const ACHIEVEMENTS: &[Achievement] = &[
+    Achievement {
+        id: "first-session",
+        name: "First Steps",
+        description: "Complete your first typing session",
+        condition: Condition::RunsCompleted(1),
+    },
+    Achievement {
+        id: "speed-demon-50",
+        name: "Speed Demon I",
+        description: "Reach 50 WPM",
+        condition: Condition::Wpm(50),
+    },
+    Achievement {
+        id: "speed-demon-75",
+        name: "Speed Demon II",
+        description: "Reach 75 WPM",
+        condition: Condition::Wpm(75),
+    },
+    Achievement {
+        id: "speed-demon-100",
+        name: "Speed Master",
+        description: "Reach 100 WPM",
+        condition: Condition::Wpm(100),
+    },
+    Achievement {
+        id: "perfectionist",
+        name: "Perfectionist",
+        description: "Complete a level with 100% accuracy",
+        condition: Condition::AccurateRuns { accuracy: 100, runs: 1 },
+    },
+    Achievement {
+        id: "accuracy-master",
+        name: "Accuracy Master",
+        description: "Maintain 95%+ accuracy for 5 levels",
+        condition: Condition::AccurateRuns { accuracy: 95, runs: 5 },
+    },
+    Achievement {
+        id: "marathon-runner",
+        name: "Marathon Runner",
+        description: "Type 1000 letters in total",
+        condition: Condition::CharactersTyped(1000),
+    },
+    Achievement {
+        id: "combo-king-50",
+        name: "Combo King",
+        description: "Reach a 50x combo",
+        condition: Condition::Combo(50),
+    },
+    Achievement {
+        id: "combo-master-100",
+        name: "Combo Master",
+        description: "Reach a 100x combo",
+        condition: Condition::Combo(100),
+    },
+    Achievement {
+        id: "level-5",
+        name: "Upper Row Adept",
+        description: "Complete Level 5",
+        condition: Condition::LevelCleared(5),
+    },
+    Achievement {
+        id: "level-10",
+        name: "Lower Row Master",
+        description: "Complete Level 10",
+        condition: Condition::LevelCleared(10),
+    },
+    Achievement {
+        id: "level-15",
+        name: "Number Ninja",
+        description: "Complete Level 15",
+        condition: Condition::LevelCleared(15),
+    },
+    Achievement {
+        id: "level-20",
+        name: "Ultimate Champion",
+        description: "Complete Level 20 (Final Boss)",
+        condition: Condition::LevelCleared(20),
+    },
+    Achievement {
+        id: "word-wizard",
+        name: "Word Wizard",
+        description: "Type 50 words correctly",
+        condition: Condition::WordsTyped(50),
+    },
+    Achievement {
+        id: "week-warrior",
+        name: "Week Warrior",
+        description: "Play for 7 days in a row",
+        condition: Condition::StreakDays(7),
+    },
+];
+
+/// Progress towards every achievement, saved in the profile.
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct AchievementTracker {
+    #[serde(default)]
+    pub unlocked: BTreeSet<String>,
+    /// Progress by achievement id; unlocked ones keep their final value.
+    #[serde(default)]
+    pub progress: BTreeMap<String, u32>,
+    /// Last day played, in days since the Unix epoch.
+    #[serde(default)]
+    pub last_day: Option<i64>,
+    /// Consecutive days played up to `last_day`.
+    #[serde(default)]
+    pub streak: u32,
+}
+
+impl AchievementTracker {
+    pub fn is_unlocked(&self, id: &str) -> bool {
+        self.unlocked.contains(id)
+    }
+
+    /// Progress towards `achievement`, capped at its target.
+    pub fn progress(&self, achievement: &Achievement) -> u32 {
+        let target = achievement.condition.target();
+        self.progress.get(achievement.id).copied().unwrap_or(0).min(target)
+    }
+
+    /// Feeds one simulation event in; returns what it unlocked.
+    pub fn observe_event(&mut self, event: &GameEvent) -> Vec<&'static Achievement> {
+        self.apply(|condition| match (condition, event) {
+            (Condition::CharactersTyped(_), GameEvent::LetterDestroyed { .. })
+            | (Condition::CharactersTyped(_), GameEvent::WordProgress { .. })
+            | (Condition::CharactersTyped(_), GameEvent::WordDestroyed { .. })
+            | (Condition::CharactersTyped(_), GameEvent::ArmorCracked { .. })
+            | (Condition::CharactersTyped(_), GameEvent::BossHit { .. })
+            | (Condition::WordsTyped(_), GameEvent::WordDestroyed { .. }) => Some(Update::Add(1)),
+            (Condition::Combo(_), &GameEvent::ComboMilestone { combo }) => {
+                Some(Update::Max(combo.max(0) as u32))
+            }
+            _ => None,
+        })
+    }
+
+    /// Feeds a finished run in. `level_number` is the level's 1-based
+    /// position in its pack.
+    pub fn observe_run(&mut self, run: &RunSummary, level_number: usize) -> Vec<&'static Achievement> {
+        if !run.completed {
+            return Vec::new();
+        }
+        self.apply(|condition| match *condition {
+            Condition::RunsCompleted(_) => Some(Update::Add(1)),
+            Condition::Wpm(_) => Some(Update::Max(run.net_wpm.max(0.0) as u32)),
+            Condition::AccurateRuns { accuracy, .. } if run.accuracy >= accuracy => Some(Update::Add(1)),
+            Condition::LevelCleared(_) => Some(Update::Max(level_number as u32)),
+            _ => None,
+        })
+    }
+
+    /// Records play on `day` (days since the Unix epoch), extending or
+    /// restarting the streak.
+    pub fn observe_day(&mut self, day: i64) -> Vec<&'static Achievement> {
+        self.streak = match self.last_day {
+            Some(last) if last == day => self.streak,
+            Some(last) if last + 1 == day => self.streak + 1,
+            _ => 1,
+        };
+        self.last_day = Some(day);
+
+        let streak = self.streak;
+        self.apply(|condition| match condition {
+            Condition::StreakDays(_) => Some(Update::Set(streak)),
+            _ => None,
+        })
+    }
+
+    /// Applies `update` to every locked achievement and unlocks those that
+    /// reach their target.
+    fn apply(&mut self, update: impl Fn(&Condition) -> Option<Update>) -> Vec<&'static Achievement> {
+        let mut unlocked = Vec::new();
+        for achievement in ACHIEVEMENTS {
+            if self.is_unlocked(achievement.id) {
+                continue;
+            }
+            let Some(update) = update(&achievement.condition) else {
+                continue;
+            };
+            let progress = self.progress.entry(achievement.id.to_string()).or_insert(0);
+            *progress = match update {
+                Update::Add(n) => *progress + n,
+                Update::Max(n) => (*progress).max(n),
+                Update::Set(n) => n,
+            };
+            if *progress >= achievement.condition.target() {
+                self.unlocked.insert(achievement.id.to_string());
+                unlocked.push(achievement);
+            }
+        }
+        unlocked
+    }
+}
@@ -0,0 +1,247 @@
+use macroquad::audio::{load_sound_from_bytes, play_sound, set_sound_volume, stop_sound, PlaySoundParams, Sound};
+use macroquad::logging::warn;
+use crate::profile::AudioSettings;
+
+/// Sounds are synthesized at this rate when the game starts.
+const SAMPLE_RATE: u32 = 22_050;
+
+/// A one-shot sound effect.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Sfx {
+    Hit,
+    WordComplete,
+    WrongKey,
+    LifeLost,
+    Combo,
+    LevelComplete,
+    GameOver,
+    BossWarning,
+    BossDefeated,
+    PowerUp,
+}
+
+impl Sfx {
+    const ALL: [Self; 10] = [
+        Self::Hit,
+        Self::WordComplete,
+        Self::WrongKey,
+        Self::LifeLost,
+        Self::Combo,
+        Self::LevelComplete,
+        Self::GameOver,
+        Self::BossWarning,
+        Self::BossDefeated,
+        Self::PowerUp,
+    ];
+
+    fn synthesize(self) -> Vec<f32> {
+        match self {
+            Self::Hit => sweep(Wave::Square, 880.0, 1320.0, 0.07),
+            Self::WordComplete => notes(Wave::Square, &[659.3, 880.0, 1108.7], 0.06),
+            Self::WrongKey => sweep(Wave::Saw, 180.0, 110.0, 0.15),
+            Self::LifeLost => mix(&sweep(Wave::Square, 300.0, 70.0, 0.4), &noise(0.25), 0.5),
+            Self::Combo => notes(Wave::Triangle, &[523.3, 659.3, 784.0, 1046.5], 0.07),
+            Self::LevelComplete => {
+                notes(Wave::Square, &[523.3, 659.3, 784.0, 1046.5, 784.0, 1046.5], 0.12)
+            }
+            Self::GameOver => notes(Wave::Triangle, &[392.0, 329.6, 261.6, 196.0], 0.25),
+            Self::BossWarning => notes(Wave::Saw, &[440.0, 330.0, 440.0, 330.0], 0.18),
+            Self::PowerUp => sweep(Wave::Triangle, 440.0, 1760.0, 0.25),
+            Self::BossDefeated => mix(&notes(Wave::Square, &[392.0, 523.3, 659.3, 784.0], 0.1), &noise(0.6), 0.4),
+        }
+    }
+}
+
+#[derive(Clone, Copy)]
+enum Wave {
+    Square,
+    Saw,
+    Triangle,
+}
+
+impl Wave {
+    /// Value at `phase` in `0.0..1.0` of one cycle.
+    fn at(self, phase: f32) -> f32 {
+        match self {
+            Self::Square => if phase < 0.5 { 1.0 } else { -1.0 },
+            Self::Saw => 2.0 * phase - 1.0,
+            Self::Triangle => 1.0 - 4.0 * (phase - 0.5).abs(),
+        }
+    }
+}
+
+/// A tone gliding from `from` to `to` Hz, with a short attack and a linear
+/// fade so it never clicks.
+fn sweep(wave: Wave, from: f32, to: f32, duration: f32) -> Vec<f32> {
+    let count = (duration * SAMPLE_RATE as f32) as usize;
+    let attack = SAMPLE_RATE as usize / 200;
+    let mut phase = 0.0;
+    (0..count)
+        .map(|i| {
+            let t = i as f32 / count as f32;
+            phase = (phase + (from + (to - from) * t) / SAMPLE_RATE as f32).fract();
+            let envelope = (i as f32 / attack as f32).min(1.0) * (1.0 - t);
+            wave.at(phase) * envelope
+        })
+        .collect()
+}
+
+/// Steady notes played one after another.
+fn notes(wave: Wave, frequencies: &[f32], length: f32) -> Vec<f32> {
+    frequencies.iter().flat_map(|&f| sweep(wave, f, f, length)).collect()
+}
+
+/// Fading white noise from a fixed seed, so every launch sounds the same.
+fn noise(duration: f32) -> Vec<f32> {
+    let count = (duration * SAMPLE_RATE as f32) as usize;
+    let mut state: u32 = 0x2545_F491;
+    (0..count)
+        .map(|i| {
+            // xorshift32
+            state ^= state << 13;
+            state ^= state >> 17;
+            state ^= state << 5;
+            let value = state as f32 / u32::MAX as f32 * 2.0 - 1.0;
+            value * (1.0 - i as f32 / count as f32)
+        })
+        .collect()
+}
+
+/// Adds `b` into `a` at `gain`, extending `a` if `b` is longer.
+fn mix(a: &[f32], b: &[f32], gain: f32) -> Vec<f32> {
+    let mut out = a.to_vec();
+    out.resize(a.len().max(b.len()), 0.0);
+    for (sample, extra) in out.iter_mut().zip(b) {
+        *sample += extra * gain;
+    }
+    out
+}
+
+/// Eight seconds of looping background music: a bass line and a soft
+/// arpeggio over A minor, F, C and G.
+fn music() -> Vec<f32> {
+    const CHORDS: [[f32; 3]; 4] = [
+        [220.0, 261.6, 329.6],
+        [174.6, 220.0, 261.6],
+        [261.6, 329.6, 392.0],
+        [196.0, 246.9, 293.7],
+    ];
+    const PATTERN: [usize; 8] = [0, 1, 2, 1, 0, 1, 2, 1];
+    let eighth = 0.25;
+
+    let mut track = Vec::new();
+    for chord in CHORDS {
+        let mut bar: Vec<f32> = PATTERN
+            .iter()
+            .flat_map(|&note| sweep(Wave::Triangle, chord[note] * 2.0, chord[note] * 2.0, eighth))
+            .map(|sample| sample * 0.3)
+            .collect();
+        let bass: Vec<f32> = (0..4)
+            .flat_map(|_| sweep(Wave::Square, chord[0] / 2.0, chord[0] / 2.0, eighth * 2.0))
+            .collect();
+        bar = mix(&bar, &bass, 0.25);
+        track.extend(bar);
+    }
+    track
+}
+
+/// Encodes samples in `-1.0..=1.0` as a mono 16-bit WAV file.
+fn to_wav(samples: &[f32]) -> Vec<u8> {
+    let data_len = samples.len() as u32 * 2;
+    let mut wav = Vec::with_capacity(44 + data_len as usize);
+    wav.extend_from_slice(b"RIFF");
+    wav.extend_from_slice(&(36 + data_len).to_le_bytes());
+    wav.extend_from_slice(b"WAVEfmt ");
+    wav.extend_from_slice(&16u32.to_le_bytes());
+    wav.extend_from_slice(&1u16.to_le_bytes()); // PCM
+    wav.extend_from_slice(&1u16.to_le_bytes()); // mono
+    wav.extend_from_slice(&SAMPLE_RATE.to_le_bytes());
+    wav.extend_from_slice(&(SAMPLE_RATE * 2).to_le_bytes());
+    wav.extend_from_slice(&2u16.to_le_bytes());
+    wav.extend_from_slice(&16u16.to_le_bytes());
+    wav.extend_from_slice(b"data");
+    wav.extend_from_slice(&data_len.to_le_bytes());
+    for sample in samples {
+        let value = (sample.clamp(-1.0, 1.0) * i16::MAX as f32) as i16;
+        wav.extend_from_slice(&value.to_le_bytes());
+    }
+    wav
+}
+
+async fn load(samples: &[f32]) -> Option<Sound> {
+    match load_sound_from_bytes(&to_wav(samples)).await {
+        Ok(sound) => Some(sound),
+        Err(err) => {
+            warn!("Could not load sound: {:?}", err);
+            None
+        }
+    }
+}
+
+/// Plays the synthesized effects and music at the player's volumes.
+pub struct Audio {
+    sfx: Vec<(Sfx, Sound)>,
+    music: Option<Sound>,
+    music_playing: bool,
+    settings: AudioSettings,
+}
+
+impl Audio {
+    pub async fn load(settings: AudioSettings) -> Self {
+        let mut sfx = Vec::new();
+        for effect in Sfx::ALL {
+            if let Some(sound) = load(&effect.synthesize()).await {
+                sfx.push((effect, sound));
+            }
+        }
+        Self {
+            sfx,
+            music: load(&music()).await,
+            music_playing: false,
+            settings,
+        }
+    }
+
+    pub fn settings(&self) -> AudioSettings {
+        self.settings
+    }
+
+    pub fn set_settings(&mut self, settings: AudioSettings) {
+        self.settings = settings;
+        if let Some(music) = &self.music {
+            set_sound_volume(music, self.music_volume());
+        }
+    }
+
+    pub fn play(&self, effect: Sfx) {
+        if self.settings.muted {
+            return;
+        }
+        if let Some((_, sound)) = self.sfx.iter().find(|(e, _)| *e == effect) {
+            play_sound(sound, PlaySoundParams {
+                looped: false,
+                volume: self.settings.sfx_volume,
+            });
+        }
+    }
+
+    /// Starts or stops the music loop; cheap to call every frame.
+    pub fn set_music(&mut self, on: bool) {
+        let Some(music) = &self.music else {
+            return;
+        };
+        if on && !self.music_playing {
+            play_sound(music, PlaySoundParams {
+                looped: true,
+                volume: self.music_volume(),
+            });
+        } else if !on && self.music_playing {
+            stop_sound(music);
+        }
+        self.music_playing = on;
+    }
+
+    fn music_volume(&self) -> f32 {
+        if self.settings.muted { 0.0 } else { self.settings.music_volume }
+    }
+}
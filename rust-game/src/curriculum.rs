@@ -0,0 +1,189 @@
+use std::collections::BTreeMap;
+use crate::keyboard::KeyboardLayout;
+use crate::levels::{BossConfig, Level, LevelPack, LevelPackError};
+
+/// Shape of the difficulty ramp from the first lesson to the last.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub enum Curve {
+    #[default]
+    Linear,
+    /// Gentle at first, steep near the end.
+    EaseIn,
+    /// Steep at first, flattening out near the end.
+    EaseOut,
+}
+
+impl Curve {
+    /// Maps progress `t` in `0.0..=1.0` to difficulty in `0.0..=1.0`.
+    pub fn apply(self, t: f32) -> f32 {
+        let t = t.clamp(0.0, 1.0);
+        match self {
+            Self::Linear => t,
+            Self::EaseIn => t * t,
+            Self::EaseOut => 1.0 - (1.0 - t) * (1.0 - t),
+        }
+    }
+}
+
+/// Builds a lesson sequence from a key order: every lesson adds a few keys
+/// to everything learned so far, with review and speed-challenge levels
+/// mixed in. Adjust the public fields after [`Curriculum::new`].
+#[derive(Clone, Debug)]
+pub struct Curriculum {
+    pub name: String,
+    pub layout: KeyboardLayout,
+    /// Keys in the order they are introduced.
+    pub key_order: Vec<char>,
+    /// Keys added per lesson, normally 1 or 2.
+    pub keys_per_lesson: usize,
+    /// Fall speed of the first and last level.
+    pub fall_speed: (f32, f32),
+    /// Spawn interval of the first and last level.
+    pub spawn_rate: (f32, f32),
+    pub curve: Curve,
+    pub duration: f32,
+    /// A review of all keys so far after every this many lessons; 0 for none.
+    pub review_every: usize,
+    /// A speed challenge after every this many lessons, ending with a boss
+    /// over that section's keys; 0 for none.
+    pub challenge_every: usize,
+    /// Chance of a power-up on each letter, for every level.
+    pub power_up_rate: f32,
+}
+
+/// What a generated level is for, before pacing is applied.
+enum Kind {
+    Lesson { new_keys: Vec<char> },
+    Review,
+    /// Ends a section; `section_keys` were introduced since the last one.
+    Challenge { section_keys: Vec<char> },
+}
+
+impl Curriculum {
+    /// Fall speed and spawn interval are scaled by this in speed challenges.
+    const CHALLENGE_PACE: f32 = 1.25;
+
+    /// A curriculum teaching `layout` in its [`KeyboardLayout::learning_order`].
+    pub fn new(layout: KeyboardLayout) -> Self {
+        Self {
+            name: format!("{} Course", layout.name()),
+            layout,
+            key_order: layout.learning_order(),
+            keys_per_lesson: 2,
+            fall_speed: (100.0, 200.0),
+            spawn_rate: (2.0, 0.8),
+            curve: Curve::Linear,
+            duration: 30.0,
+            review_every: 3,
+            challenge_every: 6,
+            power_up_rate: 0.05,
+        }
+    }
+
+    /// Generates the levels and validates them as a pack.
+    pub fn generate(&self) -> Result<LevelPack, LevelPackError> {
+        let lessons: Vec<&[char]> = self.key_order.chunks(self.keys_per_lesson.max(1)).collect();
+
+        // Lay out the sequence first so pacing can follow overall progress
+        let mut plan = Vec::new();
+        for (i, keys) in lessons.iter().enumerate() {
+            let learned: Vec<char> = lessons[..=i].concat();
+            plan.push((Kind::Lesson { new_keys: keys.to_vec() }, learned.clone()));
+
+            let done = i + 1;
+            if done == lessons.len() {
+                break;
+            }
+            if self.review_every > 0 && done % self.review_every == 0 {
+                plan.push((Kind::Review, learned.clone()));
+            }
+            if self.challenge_every > 0 && done % self.challenge_every == 0 {
+                let section_keys = lessons[done - self.challenge_every..done].concat();
+                plan.push((Kind::Challenge { section_keys }, learned));
+            }
+        }
+
+        let last = plan.len().saturating_sub(1).max(1) as f32;
+        let levels = plan
+            .into_iter()
+            .enumerate()
+            .map(|(i, (kind, letters))| self.level(i, kind, letters, i as f32 / last))
+            .collect();
+
+        let pack = LevelPack {
+            name: self.name.clone(),
+            layout: self.layout,
+            levels,
+        };
+        pack.validate()?;
+        Ok(pack)
+    }
+
+    fn level(&self, index: usize, kind: Kind, letters: Vec<char>, progress: f32) -> Level {
+        let difficulty = self.curve.apply(progress);
+        let lerp = |(start, end): (f32, f32)| start + (end - start) * difficulty;
+        let mut fall_speed = lerp(self.fall_speed);
+        let mut spawn_rate = lerp(self.spawn_rate);
+        let key_list = |keys: &[char]| {
+            keys.iter().map(char::to_string).collect::<Vec<_>>().join(" ")
+        };
+
+        let mut boss = None;
+        let (name, description, hint) = match kind {
+            Kind::Lesson { new_keys } => {
+                let fingers: Vec<String> = new_keys
+                    .iter()
+                    .filter_map(|&c| {
+                        let position = self.layout.position(c)?;
+                        Some(format!("{} with your {} finger", c, position.describe()))
+                    })
+                    .collect();
+                let names: Vec<String> = new_keys.iter().map(char::to_string).collect();
+                (
+                    format!("New Keys: {}", names.join(" & ")),
+                    format!("Add {} to your keys.", names.join(" and ")),
+                    format!("Type {}. Return to the home row after each key.", fingers.join(", ")),
+                )
+            }
+            Kind::Review => (
+                "Review".to_string(),
+                format!("Everything so far: {}", key_list(&letters)),
+                "Accuracy first - slow down on the keys that still feel new.".to_string(),
+            ),
+            Kind::Challenge { section_keys } => {
+                fall_speed *= Self::CHALLENGE_PACE;
+                spawn_rate /= Self::CHALLENGE_PACE;
+                // The section's keys first, then everything, newest first
+                let names = |keys: &[char]| keys.iter().map(char::to_string).collect();
+                let newest_first: Vec<char> = letters.iter().rev().copied().collect();
+                boss = Some(BossConfig {
+                    name: "Section Guardian".to_string(),
+                    phases: vec![names(&section_keys), names(&newest_first)],
+                });
+                (
+                    "Speed Challenge".to_string(),
+                    "All letters you've learned - faster!".to_string(),
+                    "Stay relaxed and keep a steady rhythm.".to_string(),
+                )
+            }
+        };
+
+        // Namespaced by layout so records never mix with the default pack's
+        // or another layout's course
+        Level {
+            id: format!("{}-{}", self.layout.name().to_lowercase(), index + 1),
+            name,
+            letters,
+            words: Vec::new(),
+            fall_speed,
+            spawn_rate,
+            duration: self.duration,
+            description,
+            hint,
+            case_sensitive: false,
+            behaviors: BTreeMap::new(),
+            power_up_rate: self.power_up_rate,
+            boss,
+        }
+    }
+}
@@ -0,0 +1,111 @@
+use rand::{Rng, SeedableRng};
+use rand_chacha::ChaCha8Rng;
+use std::collections::BTreeMap;
+use crate::keyboard::KeyboardLayout;
+use crate::levels::Level;
+
+/// Prefix of every daily level id; the date follows.
+const ID_PREFIX: &str = "daily-";
+
+/// Mixed into the day number so daily seeds do not collide with small
+/// hand-picked seeds.
+const SEED_SALT: u64 = 0xD1B5_4A32_D192_ED03;
+
+/// The same generated level and seed for every player on a given UTC day.
+#[derive(Clone, Debug)]
+pub struct DailyChallenge {
+    /// Days since the Unix epoch.
+    pub day: i64,
+    pub seed: u64,
+    /// Written for QWERTY; remap it for other layouts.
+    pub level: Level,
+}
+
+impl DailyChallenge {
+    pub fn for_day(day: i64) -> Self {
+        let seed = splitmix64(day as u64 ^ SEED_SALT);
+        let mut rng = ChaCha8Rng::seed_from_u64(seed);
+
+        // A shuffled pick of 6 to 12 letter keys; u32 draws keep wasm32 and
+        // 64-bit hosts in step
+        let mut pool: Vec<char> = KeyboardLayout::Qwerty
+            .learning_order()
+            .into_iter()
+            .filter(|c| c.is_alphabetic())
+            .collect();
+        let count = rng.gen_range(6..=12u32) as usize;
+        let mut letters = Vec::with_capacity(count);
+        for _ in 0..count {
+            let index = rng.gen_range(0..pool.len() as u32) as usize;
+            letters.push(pool.remove(index));
+        }
+
+        let date = date_string(day);
+        let level = Level {
+            id: format!("{}{}", ID_PREFIX, date),
+            name: format!("Daily Challenge {}", date),
+            letters,
+            words: Vec::new(),
+            fall_speed: rng.gen_range(120.0..180.0),
+            spawn_rate: rng.gen_range(0.9..1.5),
+            duration: 60.0,
+            description: "Everyone gets the same letters today. Make them count!".to_string(),
+            hint: "Only your best score of the day is kept.".to_string(),
+            case_sensitive: false,
+            behaviors: BTreeMap::new(),
+            power_up_rate: 0.05,
+            boss: None,
+        };
+        Self { day, seed, level }
+    }
+
+    /// The challenge a daily level id such as `daily-2026-10-17` came from.
+    pub fn from_level_id(id: &str) -> Option<Self> {
+        let date = id.strip_prefix(ID_PREFIX)?;
+        let mut parts = date.splitn(3, '-').map(|part| part.parse::<i64>().ok());
+        let (year, month, day) = (parts.next()??, parts.next()??, parts.next()??);
+        Some(Self::for_day(days_from_civil(year, month, day)))
+    }
+
+    pub fn date(&self) -> String {
+        date_string(self.day)
+    }
+}
+
+/// UTC day number for a Unix timestamp in seconds.
+pub fn day_of(timestamp: f64) -> i64 {
+    (timestamp / 86_400.0).floor() as i64
+}
+
+/// `YYYY-MM-DD` for days since the Unix epoch.
+pub fn date_string(day: i64) -> String {
+    // Howard Hinnant's civil_from_days
+    let z = day + 719_468;
+    let era = z.div_euclid(146_097);
+    let doe = z.rem_euclid(146_097);
+    let yoe = (doe - doe / 1_460 + doe / 36_524 - doe / 146_096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let d = doy - (153 * mp + 2) / 5 + 1;
+    let m = if mp < 10 { mp + 3 } else { mp - 9 };
+    let y = yoe + era * 400 + i64::from(m <= 2);
+    format!("{:04}-{:02}-{:02}", y, m, d)
+}
+
+/// Inverse of [`date_string`].
+fn days_from_civil(year: i64, month: i64, day: i64) -> i64 {
+    let y = if month <= 2 { year - 1 } else { year };
+    let era = y.div_euclid(400);
+    let yoe = y.rem_euclid(400);
+    let mp = (month + 9) % 12;
+    let doy = (153 * mp + 2) / 5 + day - 1;
+    let doe = yoe * 365 + yoe / 4 - yoe / 100 + doy;
+    era * 146_097 + doe - 719_468
+}
+
+fn splitmix64(mut x: u64) -> u64 {
+    x = x.wrapping_add(0x9E37_79B9_7F4A_7C15);
+    x = (x ^ (x >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+    x = (x ^ (x >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+    x ^ (x >> 31)
+}
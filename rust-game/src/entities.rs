@@ -1,5 +1,59 @@
 use macroquad::prelude::*;
-use macroquad::rand::gen_range;
+use ::rand::Rng;
+use serde::{Deserialize, Serialize};
+
+/// How a falling letter moves and takes hits.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Behavior {
+    /// Falls straight down at a constant speed.
+    #[default]
+    Normal,
+    /// Sways from side to side as it falls.
+    Zigzag,
+    /// Starts at the normal speed and keeps speeding up.
+    Accelerating,
+    /// Needs two presses: the first cracks its armor.
+    Armored,
+    /// Breaks into two new letters when destroyed.
+    Splitter,
+}
+
+/// Effect granted by typing a glowing power-up letter.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum PowerUp {
+    /// Absorbs the next target reaching the ground.
+    Shield,
+    /// Falling targets move at reduced speed for a while.
+    SlowTime,
+    /// Destroys every target on screen.
+    Bomb,
+    /// One more life, up to the maximum.
+    ExtraLife,
+}
+
+impl PowerUp {
+    pub const ALL: [Self; 4] = [Self::Shield, Self::SlowTime, Self::Bomb, Self::ExtraLife];
+
+    /// Short tag drawn above the letter.
+    pub fn label(self) -> &'static str {
+        match self {
+            Self::Shield => "SHIELD",
+            Self::SlowTime => "SLOW",
+            Self::Bomb => "BOMB",
+            Self::ExtraLife => "+1 LIFE",
+        }
+    }
+
+    pub fn color(self) -> Color {
+        match self {
+            Self::Shield => Color::from_rgba(80, 160, 255, 255),
+            Self::SlowTime => Color::from_rgba(200, 120, 255, 255),
+            Self::Bomb => Color::from_rgba(255, 90, 40, 255),
+            Self::ExtraLife => Color::from_rgba(255, 60, 120, 255),
+        }
+    }
+}
 
 #[derive(Clone, Debug)]
 pub struct Letter {
@@ -10,9 +64,28 @@ pub struct Letter {
     pub size: f32,
     pub color: Color,
     pub is_targeted: bool,
+    /// Simulation time the letter appeared, for reaction-time stats.
+    pub spawned_at: f32,
+    pub behavior: Behavior,
+    /// Presses still needed before a hit destroys the letter.
+    pub armor: u32,
+    /// Centre of a zigzag letter's sway.
+    pub base_x: f32,
+    /// Seconds since the letter appeared, driving the sway.
+    pub age: f32,
+    /// Came out of a splitter rather than the spawner.
+    pub fragment: bool,
+    /// Granted when the letter is destroyed.
+    pub power_up: Option<PowerUp>,
 }
 
 impl Letter {
+    /// Side-to-side reach of a zigzag letter in pixels.
+    pub const ZIGZAG_AMPLITUDE: f32 = 50.0;
+    const ZIGZAG_FREQUENCY: f32 = 3.0;
+    /// Speed gained per second by accelerating letters.
+    const ACCELERATION: f32 = 60.0;
+
     pub fn new(char: char, x: f32, speed: f32) -> Self {
         Self {
             char,
@@ -22,20 +95,107 @@ impl Letter {
             size: 40.0,
             color: Color::from_rgba(0, 240, 255, 255), // Neon cyan
             is_targeted: false,
+            spawned_at: 0.0,
+            behavior: Behavior::Normal,
+            armor: 0,
+            base_x: x,
+            age: 0.0,
+            fragment: false,
+            power_up: None,
         }
     }
 
+    /// Switches to `behavior`, with its armor and color.
+    pub fn set_behavior(&mut self, behavior: Behavior) {
+        self.behavior = behavior;
+        self.armor = u32::from(behavior == Behavior::Armored);
+        self.color = match behavior {
+            Behavior::Normal => Color::from_rgba(0, 240, 255, 255),
+            Behavior::Zigzag => Color::from_rgba(180, 90, 255, 255),
+            Behavior::Accelerating => Color::from_rgba(255, 140, 0, 255),
+            Behavior::Armored => Color::from_rgba(190, 200, 210, 255),
+            Behavior::Splitter => Color::from_rgba(57, 255, 20, 255),
+        };
+    }
+
     pub fn update(&mut self, delta: f32) {
+        self.age += delta;
+        match self.behavior {
+            Behavior::Zigzag => {
+                self.x = self.base_x + (self.age * Self::ZIGZAG_FREQUENCY).sin() * Self::ZIGZAG_AMPLITUDE;
+            }
+            Behavior::Accelerating => self.speed += Self::ACCELERATION * delta,
+            _ => {}
+        }
         self.y += self.speed * delta;
     }
 
     pub fn draw(&self) {
+        let glow = Color::new(self.color.r, self.color.g, self.color.b, 0.2);
+
+        // Behavior cues behind the letter
+        match self.behavior {
+            Behavior::Normal => {}
+            Behavior::Zigzag => {
+                // Wavy trail of where it swayed
+                for i in 1..4 {
+                    let age = self.age - i as f32 * 0.08;
+                    let x = self.base_x + (age * Self::ZIGZAG_FREQUENCY).sin() * Self::ZIGZAG_AMPLITUDE;
+                    let y = self.y - self.speed * i as f32 * 0.08;
+                    draw_circle(x, y, self.size * 0.5 / i as f32, glow);
+                }
+            }
+            Behavior::Accelerating => {
+                // Speed streaks that lengthen as it gets faster
+                let length = self.speed * 0.3;
+                for offset in [-0.4, 0.0, 0.4] {
+                    let x = self.x + self.size * offset;
+                    draw_line(x, self.y - self.size * 0.6, x, self.y - self.size * 0.6 - length, 2.0, glow);
+                }
+            }
+            Behavior::Armored | Behavior::Splitter => {}
+        }
+
         // Draw glow effect
-        draw_circle(self.x, self.y, self.size * 0.8, Color::from_rgba(0, 240, 255, 50));
+        draw_circle(self.x, self.y, self.size * 0.8, glow);
+
+        // Power-ups pulse in their own color and carry a tag
+        if let Some(power_up) = self.power_up {
+            let color = power_up.color();
+            let pulse = 0.3 + 0.2 * (self.age * 6.0).sin();
+            draw_circle(self.x, self.y, self.size * 1.1, Color::new(color.r, color.g, color.b, pulse));
+            let dims = measure_text(power_up.label(), None, 16, 1.0);
+            draw_text(power_up.label(), self.x - dims.width / 2.0, self.y - self.size * 1.15, 16.0, color);
+        }
 
         // Draw letter background
         draw_circle(self.x, self.y, self.size * 0.6, Color::from_rgba(0, 100, 120, 200));
 
+        match self.behavior {
+            Behavior::Armored if self.armor > 0 => {
+                draw_circle_lines(self.x, self.y, self.size * 0.65, 5.0, self.color);
+            }
+            Behavior::Armored => {
+                // Cracked: only fragments of the plating remain
+                for start in [0.3f32, 2.4, 4.5] {
+                    let (a, b) = (start, start + 0.9);
+                    draw_line(
+                        self.x + a.cos() * self.size * 0.65,
+                        self.y + a.sin() * self.size * 0.65,
+                        self.x + b.cos() * self.size * 0.65,
+                        self.y + b.sin() * self.size * 0.65,
+                        3.0,
+                        self.color,
+                    );
+                }
+            }
+            Behavior::Splitter => {
+                // Seam it will break along
+                draw_line(self.x, self.y - self.size * 0.8, self.x, self.y + self.size * 0.8, 2.0, self.color);
+            }
+            _ => {}
+        }
+
         // Draw letter
         let text = self.char.to_string();
         let font_size = (self.size * 1.2) as u16;
@@ -55,8 +215,308 @@ impl Letter {
         }
     }
 
-    pub fn is_off_screen(&self, screen_height: f32) -> bool {
-        self.y > screen_height + 50.0
+    pub fn has_landed(&self, ground_y: f32) -> bool {
+        self.y >= ground_y
+    }
+
+    /// Whether any of it shows below the top edge, so it can be typed.
+    pub fn is_in_view(&self) -> bool {
+        self.y > -self.size
+    }
+}
+
+/// A word or phrase that is typed character by character. Once its first
+/// character is typed it stays locked as the active target until finished.
+#[derive(Clone, Debug)]
+pub struct Word {
+    pub text: Vec<char>,
+    pub typed: usize,
+    pub x: f32,
+    pub y: f32,
+    pub speed: f32,
+    pub size: f32,
+    pub is_locked: bool,
+    /// Simulation time of the last typed character (or the spawn).
+    pub last_hit_at: f32,
+}
+
+impl Word {
+    pub fn new(text: &str, x: f32, speed: f32) -> Self {
+        Self {
+            text: text.chars().collect(),
+            typed: 0,
+            x,
+            y: -50.0,
+            speed,
+            size: 28.0,
+            is_locked: false,
+            last_hit_at: 0.0,
+        }
+    }
+
+    pub fn update(&mut self, delta: f32) {
+        self.y += self.speed * delta;
+    }
+
+    /// The character that must be typed next, if any remain.
+    pub fn next_char(&self) -> Option<char> {
+        self.text.get(self.typed).copied()
+    }
+
+    pub fn is_complete(&self) -> bool {
+        self.typed >= self.text.len()
+    }
+
+    /// Approximate on-screen width, used to keep spawns inside the field.
+    pub fn width(&self) -> f32 {
+        self.text.len() as f32 * self.size * 0.6 + 20.0
+    }
+
+    pub fn draw(&self) {
+        let font_size = self.size as u16;
+        let typed: String = self.text[..self.typed].iter().collect();
+        let remaining: String = self.text[self.typed..].iter().collect();
+        let typed_dims = measure_text(&typed, None, font_size, 1.0);
+        let remaining_dims = measure_text(&remaining, None, font_size, 1.0);
+        let total_width = typed_dims.width + remaining_dims.width;
+        let left = self.x - total_width / 2.0;
+
+        // Draw word background
+        draw_rectangle(
+            left - 10.0,
+            self.y - self.size * 0.7,
+            total_width + 20.0,
+            self.size * 1.2,
+            Color::from_rgba(0, 100, 120, 200),
+        );
+
+        // Typed characters dim out, the rest stay bright
+        draw_text(&typed, left, self.y + self.size * 0.2, self.size, Color::from_rgba(57, 255, 20, 255));
+        draw_text(
+            &remaining,
+            left + typed_dims.width,
+            self.y + self.size * 0.2,
+            self.size,
+            WHITE,
+        );
+
+        // Draw lock-on indicator
+        if self.is_locked {
+            draw_rectangle_lines(
+                left - 12.0,
+                self.y - self.size * 0.7 - 2.0,
+                total_width + 24.0,
+                self.size * 1.2 + 4.0,
+                3.0,
+                YELLOW,
+            );
+        }
+    }
+
+    pub fn has_landed(&self, ground_y: f32) -> bool {
+        self.y >= ground_y
+    }
+
+    /// Whether any of it shows below the top edge, so it can be typed.
+    pub fn is_in_view(&self) -> bool {
+        self.y > -self.size
+    }
+}
+
+/// What a correct key did to a [`Boss`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum BossProgress {
+    Chipped,
+    /// The phase's sequence is done and the next one begins.
+    PhaseCleared,
+    Defeated,
+}
+
+/// A large ship that ends a level. Each phase carries a sequence of
+/// characters or words typed in order; every correct key chips one point
+/// of health.
+#[derive(Clone, Debug)]
+pub struct Boss {
+    pub name: String,
+    pub phases: Vec<Vec<Vec<char>>>,
+    pub phase: usize,
+    /// Entry of the current phase being typed.
+    pub entry: usize,
+    /// Characters typed of that entry.
+    pub typed: usize,
+    pub health: u32,
+    pub max_health: u32,
+    pub x: f32,
+    pub y: f32,
+    /// Centre of the side-to-side sway.
+    pub home_x: f32,
+    /// Height the ship descends to and holds.
+    pub target_y: f32,
+    pub size: f32,
+    /// Seconds since the boss appeared, driving the sway.
+    pub age: f32,
+    /// Simulation time of the last hit (or the arrival).
+    pub last_hit_at: f32,
+}
+
+impl Boss {
+    pub fn new(name: &str, phases: &[Vec<String>], x: f32) -> Self {
+        let phases: Vec<Vec<Vec<char>>> = phases
+            .iter()
+            .map(|phase| phase.iter().map(|entry| entry.chars().collect()).collect())
+            .collect();
+        let max_health = phases.iter().flatten().map(|entry| entry.len() as u32).sum();
+        Self {
+            name: name.to_string(),
+            phases,
+            phase: 0,
+            entry: 0,
+            typed: 0,
+            health: max_health,
+            max_health,
+            x,
+            y: -80.0,
+            home_x: x,
+            target_y: 130.0,
+            size: 60.0,
+            age: 0.0,
+            last_hit_at: 0.0,
+        }
+    }
+
+    pub fn update(&mut self, delta: f32) {
+        self.age += delta;
+        self.y = (self.y + 80.0 * delta).min(self.target_y);
+        self.x = self.home_x + (self.age * 0.8).sin() * 80.0;
+    }
+
+    /// The character that must be typed next, if any remain.
+    pub fn next_char(&self) -> Option<char> {
+        self.current_entry()?.get(self.typed).copied()
+    }
+
+    fn current_entry(&self) -> Option<&[char]> {
+        self.phases.get(self.phase)?.get(self.entry).map(Vec::as_slice)
+    }
+
+    /// Accepts the next character, moving on to the next entry and phase as
+    /// each is finished.
+    pub fn advance(&mut self) -> BossProgress {
+        self.typed += 1;
+        self.health = self.health.saturating_sub(1);
+        if self.current_entry().is_some_and(|entry| self.typed < entry.len()) {
+            return BossProgress::Chipped;
+        }
+
+        self.typed = 0;
+        self.entry += 1;
+        if self.entry < self.phases[self.phase].len() {
+            return BossProgress::Chipped;
+        }
+
+        self.entry = 0;
+        self.phase += 1;
+        if self.phase < self.phases.len() {
+            BossProgress::PhaseCleared
+        } else {
+            BossProgress::Defeated
+        }
+    }
+
+    pub fn is_defeated(&self) -> bool {
+        self.phase >= self.phases.len()
+    }
+
+    pub fn draw(&self) {
+        let hull = Color::from_rgba(255, 0, 110, 255);
+        let glow = Color::from_rgba(255, 0, 110, 50);
+
+        // Draw hull with swept wings
+        draw_circle(self.x, self.y, self.size * 1.3, glow);
+        draw_triangle(
+            Vec2::new(self.x - self.size * 1.6, self.y - self.size * 0.3),
+            Vec2::new(self.x + self.size * 1.6, self.y - self.size * 0.3),
+            Vec2::new(self.x, self.y + self.size * 0.6),
+            hull,
+        );
+        draw_rectangle(
+            self.x - self.size * 0.6,
+            self.y - self.size * 0.6,
+            self.size * 1.2,
+            self.size * 0.8,
+            Color::from_rgba(120, 0, 60, 255),
+        );
+        draw_circle(self.x, self.y - self.size * 0.2, self.size * 0.25, Color::from_rgba(0, 240, 255, 255));
+
+        // Health bar, split into one chip per phase
+        let bar_width = self.size * 3.0;
+        let left = self.x - bar_width / 2.0;
+        let bar_y = self.y + self.size * 0.8;
+        draw_rectangle(left, bar_y, bar_width, 8.0, Color::from_rgba(60, 0, 30, 255));
+        let filled = bar_width * self.health as f32 / self.max_health.max(1) as f32;
+        draw_rectangle(left + bar_width - filled, bar_y, filled, 8.0, hull);
+        let mut chip_x = left;
+        for phase in self.phases.iter().take(self.phases.len().saturating_sub(1)) {
+            let phase_health: usize = phase.iter().map(Vec::len).sum();
+            chip_x += bar_width * phase_health as f32 / self.max_health.max(1) as f32;
+            draw_line(chip_x, bar_y - 2.0, chip_x, bar_y + 10.0, 2.0, WHITE);
+        }
+
+        // The entry being typed, then the rest of the phase dimmed
+        let Some(entry) = self.current_entry() else {
+            return;
+        };
+        let font_size = 36.0;
+        let typed: String = entry[..self.typed].iter().collect();
+        let remaining: String = entry[self.typed..].iter().collect();
+        let upcoming: Vec<String> = self.phases[self.phase][self.entry + 1..]
+            .iter()
+            .map(|entry| entry.iter().collect())
+            .collect();
+        let upcoming = format!("  {}", upcoming.join(" "));
+        let typed_width = measure_text(&typed, None, font_size as u16, 1.0).width;
+        let remaining_width = measure_text(&remaining, None, font_size as u16, 1.0).width;
+        let text_x = self.x - (typed_width + remaining_width) / 2.0;
+        let text_y = bar_y + 45.0;
+        draw_text(&typed, text_x, text_y, font_size, Color::from_rgba(57, 255, 20, 255));
+        draw_text(&remaining, text_x + typed_width, text_y, font_size, WHITE);
+        draw_text(&upcoming, text_x + typed_width + remaining_width, text_y, 24.0, GRAY);
+    }
+}
+
+/// Shot a boss fires at the player after a wrong key.
+#[derive(Clone, Debug)]
+pub struct BossShot {
+    pub x: f32,
+    pub y: f32,
+    pub vx: f32,
+    pub vy: f32,
+}
+
+impl BossShot {
+    /// A shot from `x`, `y` flying straight at `target_x`, `target_y`.
+    pub fn new(x: f32, y: f32, target_x: f32, target_y: f32, speed: f32) -> Self {
+        let velocity = Vec2::new(target_x - x, target_y - y).normalize_or_zero() * speed;
+        Self {
+            x,
+            y,
+            vx: velocity.x,
+            vy: velocity.y,
+        }
+    }
+
+    pub fn update(&mut self, delta: f32) {
+        self.x += self.vx * delta;
+        self.y += self.vy * delta;
+    }
+
+    pub fn draw(&self) {
+        draw_circle(self.x, self.y, 12.0, Color::from_rgba(255, 51, 102, 80));
+        draw_circle(self.x, self.y, 6.0, Color::from_rgba(255, 200, 220, 255));
+    }
+
+    pub fn has_hit(&self, target_y: f32) -> bool {
+        self.y >= target_y
     }
 }
 
@@ -73,9 +533,9 @@ pub struct Particle {
 }
 
 impl Particle {
-    pub fn new(x: f32, y: f32) -> Self {
-        let angle = gen_range(0.0, std::f32::consts::TAU);
-        let speed = gen_range(100.0, 300.0);
+    pub fn new(x: f32, y: f32, rng: &mut impl Rng) -> Self {
+        let angle = rng.gen_range(0.0..std::f32::consts::TAU);
+        let speed = rng.gen_range(100.0..300.0);
 
         Self {
             x,
@@ -84,7 +544,7 @@ impl Particle {
             vy: angle.sin() * speed,
             life: 1.0,
             max_life: 1.0,
-            size: gen_range(3.0, 8.0),
+            size: rng.gen_range(3.0..8.0),
             color: Color::new(0.0, 0.94, 1.0, 1.0),
         }
     }
@@ -1,235 +1,631 @@
 use macroquad::prelude::*;
-use macroquad::rand::gen_range;
-use std::collections::HashMap;
-use crate::entities::{Letter, Particle, Player};
-use crate::levels::Level;
+use ::rand::{Rng, SeedableRng};
+use rand_chacha::ChaCha8Rng;
+use crate::achievements::{Achievement, ACHIEVEMENTS};
+use crate::audio::{Audio, Sfx};
+use crate::entities::{Particle, PowerUp};
+use crate::daily::{self, DailyChallenge};
+use crate::keyboard::{KeyboardLayout, ROW_STAGGER};
+use crate::layout::{Layout, VIRTUAL_HEIGHT, VIRTUAL_WIDTH};
+use crate::levels::{Level, LevelPack};
+use crate::profile::{AudioSettings, Leaderboard, Profile};
+use crate::replay::Replay;
+use crate::simulation::{GameEvent, InputEvent, RunMode, Simulation, SLOW_TIME_DURATION, SPLIT_SIZE};
+use crate::spawner::SpawnerConfig;
+
+/// Accuracy band adaptive difficulty tries to hold the player in.
+const ADAPTIVE_BAND: SpawnerConfig = SpawnerConfig::Adaptive {
+    min_accuracy: 0.90,
+    max_accuracy: 0.95,
+};
+
+/// A frame longer than this pauses the run. macroquad has no focus event,
+/// so on native builds a stalled frame is the only signal: a window that
+/// loses focus but keeps rendering is not paused. The browser build also
+/// pauses on `blur`/`visibilitychange`, see [`focus_lost`].
+const AUTO_PAUSE_GAP: f32 = 0.25;
+
+/// Seconds counted down between the briefing and play.
+const COUNTDOWN: f32 = 3.0;
+
+/// Rows of the level list visible at once.
+const LEVEL_SELECT_ROWS: usize = 8;
+
+const PAUSE_OPTIONS: [&str; 6] = ["Resume", "Restart", "SFX volume", "Music volume", "Sound", "Quit to menu"];
+
+/// Seconds an achievement toast stays on screen.
+const TOAST_TIME: f32 = 3.0;
+
+/// Seconds a boss warning or phase banner stays on screen.
+const BOSS_BANNER_TIME: f32 = 2.0;
+
+/// Seconds the HUD shows a bomb or extra life after it is collected.
+const POWER_UP_NOTICE_TIME: f32 = 1.5;
+
+/// Volume change per LEFT/RIGHT press in the pause menu.
+const VOLUME_STEP: f32 = 0.1;
+
+/// Mixed into the run seed so cosmetic effects draw from their own stream
+/// and can never shift the gameplay sequence.
+const FX_STREAM: u64 = 0x9E37_79B9_7F4A_7C15;
 
 #[derive(PartialEq, Clone, Copy)]
 pub enum GameState {
     Menu,
+    LevelSelect,
+    Achievements,
+    LevelBriefing,
     Playing,
+    Paused,
     LevelComplete,
     GameOver,
 }
 
+/// Which levels runs are played on.
+#[derive(Clone)]
+enum Mode {
+    /// The level pack, level by level.
+    Campaign,
+    /// Today's generated level with its fixed seed.
+    Daily(Box<DailyChallenge>),
+}
+
+/// Unlock notification shown over whatever screen is active.
+struct Toast {
+    title: String,
+    text: String,
+    time_left: f32,
+}
+
+/// Characters typed since the last frame, in order, without the OS
+/// auto-repeats of held keys.
+struct TypedChars(Vec<char>);
+
+impl miniquad::EventHandler for TypedChars {
+    fn update(&mut self) {}
+
+    fn draw(&mut self) {}
+
+    fn char_event(&mut self, character: char, _modifiers: miniquad::KeyMods, repeat: bool) {
+        if !repeat {
+            self.0.push(character);
+        }
+    }
+}
+
+/// Macroquad front end: reads the keyboard, steps the [`Simulation`] and
+/// turns its events into particles, shake and flashes.
 pub struct Game {
     state: GameState,
-    player: Player,
-    letters: Vec<Letter>,
+    sim: Simulation,
+    /// Subscription to raw miniquad input, for typed characters.
+    input_subscriber: usize,
+    /// The pack as loaded, in the layout it was written for.
+    source_pack: LevelPack,
+    /// `source_pack` remapped to the player's keyboard layout.
+    pack: LevelPack,
+    profile: Profile,
+    audio: Audio,
     particles: Vec<Particle>,
+    mode: Mode,
+    /// Rules for campaign runs, chosen on the menu.
+    run_mode: RunMode,
     current_level: usize,
-    level: Level,
-    score: i32,
-    combo: i32,
-    correct_count: i32,
-    total_count: i32,
-    spawn_timer: f32,
-    level_timer: f32,
-    last_key_time: f32,
+    /// Highlighted row on the level select screen.
+    select_index: usize,
+    next_seed: Option<u64>,
+    spawner: SpawnerConfig,
+    pending_replay: Option<Replay>,
+    replay_status: Option<String>,
+    /// Leaderboard rank of the last sudden-death run, if it made the board.
+    last_rank: Option<usize>,
+    /// Whether the last time-attack run set a new best time.
+    new_best_time: bool,
+    pause_selection: usize,
+    countdown: Option<f32>,
+    fx_rng: ChaCha8Rng,
     screen_shake: f32,
-    typed_letters: HashMap<char, i32>,
-    errors: HashMap<char, i32>,
+    shake_offset: Vec2,
     flash_timer: f32,
     flash_color: Color,
+    toasts: Vec<Toast>,
+    /// Boss warning or phase banner and its seconds left.
+    boss_banner: Option<(String, f32)>,
+    /// One-shot power-up just collected and its seconds left on the HUD.
+    power_up_notice: Option<(PowerUp, f32)>,
 }
 
 impl Game {
-    pub fn new() -> Self {
-        let level = Level::get_level(0);
+    pub fn new(source_pack: LevelPack, mut profile: Profile, audio: Audio) -> Self {
+        let pack = source_pack.remapped(profile.layout);
+        // The first level is always open; resume at the furthest unlocked one
+        profile.unlock(&pack.levels[0].id);
+        let current_level = pack
+            .levels
+            .iter()
+            .rposition(|level| profile.is_unlocked(&level.id))
+            .unwrap_or(0);
+        let level = pack.get_level(current_level);
         Self {
             state: GameState::Menu,
-            player: Player::new(400.0, 550.0),
-            letters: Vec::new(),
+            sim: Simulation::new(level, VIRTUAL_WIDTH, VIRTUAL_HEIGHT, 0),
+            input_subscriber: utils::register_input_subscriber(),
+            source_pack,
+            pack,
+            profile,
+            audio,
             particles: Vec::new(),
-            current_level: 0,
-            level,
-            score: 0,
-            combo: 0,
-            correct_count: 0,
-            total_count: 0,
-            spawn_timer: 0.0,
-            level_timer: 0.0,
-            last_key_time: 0.0,
+            mode: Mode::Campaign,
+            run_mode: RunMode::Normal,
+            current_level,
+            select_index: current_level,
+            next_seed: None,
+            spawner: SpawnerConfig::Uniform,
+            pending_replay: None,
+            replay_status: None,
+            last_rank: None,
+            new_best_time: false,
+            pause_selection: 0,
+            countdown: None,
+            fx_rng: ChaCha8Rng::seed_from_u64(FX_STREAM),
             screen_shake: 0.0,
-            typed_letters: HashMap::new(),
-            errors: HashMap::new(),
+            shake_offset: Vec2::ZERO,
             flash_timer: 0.0,
             flash_color: Color::from_rgba(0, 0, 0, 0),
+            toasts: Vec::new(),
+            boss_banner: None,
+            power_up_notice: None,
         }
     }
 
+    /// Forces the seed of the next run, e.g. to reproduce a bug report.
+    pub fn set_next_seed(&mut self, seed: u64) {
+        self.next_seed = Some(seed);
+    }
+
+    /// Starts playing back `replay` in place of live keyboard input.
+    pub fn play_replay(&mut self, replay: Replay) {
+        if let Some(challenge) = DailyChallenge::from_level_id(&replay.level_id) {
+            self.mode = Mode::Daily(Box::new(challenge));
+        } else if let Some(index) = self.pack.index_of(&replay.level_id) {
+            self.mode = Mode::Campaign;
+            self.current_level = index;
+        } else {
+            warn!("Replay level {} is not in the level pack", replay.level_id);
+            return;
+        }
+        self.pending_replay = Some(replay);
+        self.start_game();
+    }
+
     pub fn update(&mut self, delta: f32) {
+        // Drain typed characters every frame so presses made on other
+        // screens never leak into the next run.
+        let inputs = self.handle_input();
+
         match self.state {
             GameState::Menu => self.update_menu(),
-            GameState::Playing => self.update_playing(delta),
+            GameState::LevelSelect => self.update_level_select(),
+            GameState::Achievements => {
+                if is_key_pressed(KeyCode::Escape) || is_key_pressed(KeyCode::M) {
+                    self.state = GameState::Menu;
+                }
+            }
+            GameState::LevelBriefing => self.update_briefing(delta),
+            GameState::Playing => {
+                if is_key_pressed(KeyCode::Escape) || delta > AUTO_PAUSE_GAP || focus_lost() {
+                    self.pause();
+                } else {
+                    self.update_playing(delta, &inputs);
+                }
+            }
+            GameState::Paused => self.update_paused(),
             GameState::LevelComplete => self.update_level_complete(),
             GameState::GameOver => self.update_game_over(),
         }
+        self.audio.set_music(self.state == GameState::Playing);
+
+        for toast in &mut self.toasts {
+            toast.time_left -= delta;
+        }
+        self.toasts.retain(|toast| toast.time_left > 0.0);
     }
 
     fn update_menu(&mut self) {
         if is_key_pressed(KeyCode::Space) || is_key_pressed(KeyCode::Enter) {
-            self.start_game();
+            self.mode = Mode::Campaign;
+            self.show_briefing();
+        } else if is_key_pressed(KeyCode::D) {
+            self.mode = Mode::Daily(Box::new(DailyChallenge::for_day(today())));
+            self.show_briefing();
+        } else if is_key_pressed(KeyCode::L) {
+            self.state = GameState::LevelSelect;
+            // Endless mode lies past the list; start on the last level
+            self.select_index = self.current_level.min(self.pack.total_levels() - 1);
+        } else if is_key_pressed(KeyCode::A) {
+            self.spawner = match self.spawner {
+                SpawnerConfig::Uniform => ADAPTIVE_BAND,
+                SpawnerConfig::Adaptive { .. } => SpawnerConfig::Uniform,
+            };
+        } else if is_key_pressed(KeyCode::P) {
+            self.run_mode = self.run_mode.next();
+        } else if is_key_pressed(KeyCode::T) {
+            self.state = GameState::Achievements;
+        } else if is_key_pressed(KeyCode::K) {
+            self.set_layout(self.profile.layout.next());
+        } else if is_key_pressed(KeyCode::N) {
+            let settings = self.audio.settings();
+            self.set_audio(AudioSettings { muted: !settings.muted, ..settings });
         }
     }
 
-    fn start_game(&mut self) {
-        self.state = GameState::Playing;
-        self.player.lives = self.player.max_lives;
-        self.letters.clear();
-        self.particles.clear();
-        self.score = 0;
-        self.combo = 0;
-        self.correct_count = 0;
-        self.total_count = 0;
-        self.spawn_timer = 0.0;
-        self.level_timer = 0.0;
-        self.typed_letters.clear();
-        self.errors.clear();
-    }
-
-    fn update_playing(&mut self, delta: f32) {
-        self.level_timer += delta;
-        self.spawn_timer += delta;
-        self.screen_shake = (self.screen_shake - delta * 5.0).max(0.0);
-        self.flash_timer = (self.flash_timer - delta * 3.0).max(0.0);
+    /// Applies new volumes and remembers them in the profile.
+    fn set_audio(&mut self, settings: AudioSettings) {
+        self.audio.set_settings(settings);
+        self.profile.audio = settings;
+        if let Err(err) = self.profile.save() {
+            warn!("Could not save profile: {}", err);
+        }
+    }
+
+    /// Switches the player's keyboard layout and remaps the levels to it.
+    fn set_layout(&mut self, layout: KeyboardLayout) {
+        self.profile.layout = layout;
+        self.pack = self.source_pack.remapped(layout);
+        if let Err(err) = self.profile.save() {
+            warn!("Could not save profile: {}", err);
+        }
+    }
 
-        // Spawn new letters
-        if self.spawn_timer >= self.level.spawn_rate {
-            self.spawn_letter();
-            self.spawn_timer = 0.0;
+    fn update_level_select(&mut self) {
+        let count = self.pack.total_levels();
+        if is_key_pressed(KeyCode::Up) {
+            self.select_index = (self.select_index + count - 1) % count;
+        } else if is_key_pressed(KeyCode::Down) {
+            self.select_index = (self.select_index + 1) % count;
+        } else if is_key_pressed(KeyCode::Enter) || is_key_pressed(KeyCode::Space) {
+            if self.profile.is_unlocked(&self.pack.levels[self.select_index].id) {
+                self.mode = Mode::Campaign;
+                self.current_level = self.select_index;
+                self.show_briefing();
+            }
+        } else if is_key_pressed(KeyCode::Escape) || is_key_pressed(KeyCode::M) {
+            self.state = GameState::Menu;
         }
+    }
 
-        // Update letters
-        for letter in &mut self.letters {
-            letter.update(delta);
+    /// The level the next run is played on, remapped to `layout`.
+    fn level_for(&self, layout: KeyboardLayout) -> Level {
+        match &self.mode {
+            Mode::Campaign => self.source_pack.remapped(layout).get_level(self.current_level),
+            Mode::Daily(challenge) => challenge.level.remapped(KeyboardLayout::Qwerty, layout),
         }
+    }
 
-        // Check for letters that hit the ground
-        let screen_height = screen_height();
-        let mut to_remove = Vec::new();
-        let mut trigger_flash = false;
+    fn level(&self) -> Level {
+        match self.mode {
+            Mode::Campaign => self.pack.get_level(self.current_level),
+            Mode::Daily(_) => self.level_for(self.profile.layout),
+        }
+    }
 
-        for (i, letter) in self.letters.iter().enumerate() {
-            if letter.is_off_screen(screen_height) {
-                to_remove.push(i);
-                self.player.lives -= 1;
-                self.combo = 0;
-                trigger_flash = true;
+    /// Whether the level after the current one may be played.
+    /// Past the pack's last level lies endless mode, which opens once the
+    /// final level is cleared with the unlock accuracy.
+    fn next_level_unlocked(&self) -> bool {
+        match self.pack.levels.get(self.current_level + 1) {
+            Some(next) => self.profile.is_unlocked(&next.id),
+            None => self.pack.levels.last().and_then(|last| self.profile.record(&last.id)).is_some_and(|record| {
+                record.completions > 0 && record.best_accuracy >= Profile::UNLOCK_ACCURACY
+            }),
+        }
+    }
 
-                if self.player.lives <= 0 {
-                    self.state = GameState::GameOver;
+    /// Shows the current level's briefing; play starts after a countdown.
+    fn show_briefing(&mut self) {
+        self.state = GameState::LevelBriefing;
+        self.countdown = None;
+    }
+
+    fn update_briefing(&mut self, delta: f32) {
+        match self.countdown {
+            Some(remaining) if remaining - delta <= 0.0 => {
+                self.countdown = None;
+                self.start_game();
+            }
+            Some(remaining) => self.countdown = Some(remaining - delta),
+            None => {
+                if is_key_pressed(KeyCode::Space) || is_key_pressed(KeyCode::Enter) {
+                    self.countdown = Some(COUNTDOWN);
+                } else if is_key_pressed(KeyCode::M) {
+                    self.state = GameState::Menu;
                 }
             }
         }
+    }
 
-        if trigger_flash {
-            self.trigger_flash(Color::new(1.0, 0.2, 0.4, 0.4)); // Red flash
+    fn start_game(&mut self) {
+        self.state = GameState::Playing;
+        self.sim = match self.pending_replay.take() {
+            Some(replay) => {
+                // Replays keep the layout they were recorded on
+                let level = self.level_for(replay.layout);
+                Simulation::from_replay(&replay, level)
+            }
+            None => {
+                let level = self.level();
+                let seed = match &self.mode {
+                    Mode::Campaign => self.next_seed.take().unwrap_or_else(random_seed),
+                    Mode::Daily(challenge) => challenge.seed,
+                };
+                let mut sim = Simulation::new(level, VIRTUAL_WIDTH, VIRTUAL_HEIGHT, seed);
+                sim.set_layout(self.profile.layout);
+                // The daily challenge is always played by the normal rules
+                // with uniform spawns, so everyone gets the same sequence
+                match self.mode {
+                    Mode::Campaign => {
+                        sim.set_spawner(self.spawner);
+                        sim.set_mode(self.run_mode);
+                    }
+                    Mode::Daily(_) => {
+                        sim.set_spawner(SpawnerConfig::Uniform);
+                        sim.set_mode(RunMode::Normal);
+                    }
+                }
+                sim
+            }
+        };
+        self.fx_rng = ChaCha8Rng::seed_from_u64(self.sim.seed() ^ FX_STREAM);
+        self.particles.clear();
+        self.replay_status = None;
+        self.last_rank = None;
+        self.new_best_time = false;
+        self.boss_banner = None;
+        self.power_up_notice = None;
+    }
+
+    fn update_playing(&mut self, delta: f32, inputs: &[InputEvent]) {
+        self.screen_shake = (self.screen_shake - delta * 5.0).max(0.0);
+        self.flash_timer = (self.flash_timer - delta * 3.0).max(0.0);
+        if let Some((_, time_left)) = &mut self.boss_banner {
+            *time_left -= delta;
+            if *time_left <= 0.0 {
+                self.boss_banner = None;
+            }
+        }
+        if let Some((_, time_left)) = &mut self.power_up_notice {
+            *time_left -= delta;
+            if *time_left <= 0.0 {
+                self.power_up_notice = None;
+            }
         }
+        self.shake_offset = if self.screen_shake > 0.0 {
+            vec2(self.fx_rng.gen_range(-0.5..0.5), self.fx_rng.gen_range(-0.5..0.5))
+                * self.screen_shake * 4.0
+        } else {
+            Vec2::ZERO
+        };
 
-        // Remove letters from back to front to maintain indices
-        for i in to_remove.iter().rev() {
-            self.letters.remove(*i);
+        let events = self.sim.step(delta, inputs);
+        for event in &events {
+            self.handle_event(event);
         }
 
-        // Handle keyboard input
-        self.handle_input();
+        // A recording that stopped mid-run, such as a quit practice session
+        if self.sim.playback_ended() && self.state == GameState::Playing {
+            self.state = GameState::GameOver;
+        }
 
         // Update particles
         for particle in &mut self.particles {
             particle.update(delta);
         }
         self.particles.retain(|p| !p.is_dead());
-
-        // Check level completion
-        if self.level_timer >= self.level.duration && self.player.lives > 0 {
-            self.state = GameState::LevelComplete;
-        }
     }
 
-    fn handle_input(&mut self) {
-        // Check all letter keys
-        let keys = [
-            (KeyCode::A, 'A'), (KeyCode::B, 'B'), (KeyCode::C, 'C'), (KeyCode::D, 'D'),
-            (KeyCode::E, 'E'), (KeyCode::F, 'F'), (KeyCode::G, 'G'), (KeyCode::H, 'H'),
-            (KeyCode::I, 'I'), (KeyCode::J, 'J'), (KeyCode::K, 'K'), (KeyCode::L, 'L'),
-            (KeyCode::M, 'M'), (KeyCode::N, 'N'), (KeyCode::O, 'O'), (KeyCode::P, 'P'),
-            (KeyCode::Q, 'Q'), (KeyCode::R, 'R'), (KeyCode::S, 'S'), (KeyCode::T, 'T'),
-            (KeyCode::U, 'U'), (KeyCode::V, 'V'), (KeyCode::W, 'W'), (KeyCode::X, 'X'),
-            (KeyCode::Y, 'Y'), (KeyCode::Z, 'Z'), (KeyCode::Semicolon, ';'),
-        ];
-
-        for (keycode, character) in keys.iter() {
-            if is_key_pressed(*keycode) {
-                self.handle_letter_typed(*character);
-            }
-        }
+    fn handle_input(&self) -> Vec<InputEvent> {
+        // Character events follow the OS keyboard layout and shift state.
+        // They come through a subscriber because `get_char_pressed` cannot
+        // tell a press from an auto-repeat of a held key.
+        let mut chars = TypedChars(Vec::new());
+        utils::repeat_all_miniquad_input(&mut chars, self.input_subscriber);
+
+        chars.0.into_iter()
+            .filter(|c| !c.is_control())
+            .map(InputEvent::Char)
+            .collect()
     }
 
-    fn handle_letter_typed(&mut self, typed_char: char) {
-        self.total_count += 1;
-
-        // Find matching letter (closest to ground)
-        let mut found_index = None;
-        let mut max_y = -1.0;
-
-        for (i, letter) in self.letters.iter().enumerate() {
-            if letter.char == typed_char && letter.y > max_y {
-                found_index = Some(i);
-                max_y = letter.y;
-            }
+    fn handle_event(&mut self, event: &GameEvent) {
+        if !self.sim.is_playback() {
+            let unlocked = self.profile.achievements.observe_event(event);
+            self.show_unlocks(&unlocked);
         }
 
-        if let Some(index) = found_index {
-            // Correct letter typed!
-            let letter = self.letters.remove(index);
-            self.correct_count += 1;
-            self.combo += 1;
-
-            // Calculate points
-            let base_points = 10;
-            let combo_multiplier = 1 + (self.combo / 10);
-            let points = base_points * combo_multiplier;
-            self.score += points;
+        match *event {
+            GameEvent::LetterDestroyed { x, y, .. } => {
+                // Create explosion particles
+                for _ in 0..15 {
+                    self.particles.push(Particle::new(x, y, &mut self.fx_rng));
+                }
 
-            // Track stats
-            *self.typed_letters.entry(typed_char).or_insert(0) += 1;
+                // Screen shake
+                self.screen_shake = 2.0;
 
-            // Create explosion particles
-            for _ in 0..15 {
-                self.particles.push(Particle::new(letter.x, letter.y));
+                // Flash green
+                self.trigger_flash(Color::from_rgba(57, 255, 20, 80));
+                self.audio.play(Sfx::Hit);
             }
+            GameEvent::ArmorCracked { x, y } => {
+                for _ in 0..6 {
+                    self.particles.push(Particle::new(x, y, &mut self.fx_rng));
+                }
+                self.screen_shake = 1.0;
+                self.audio.play(Sfx::Hit);
+            }
+            GameEvent::LetterSplit { x, y } => {
+                for _ in 0..8 {
+                    self.particles.push(Particle::new(x, y, &mut self.fx_rng));
+                }
+            }
+            GameEvent::PowerUpCollected { power_up, x, y } => {
+                for _ in 0..20 {
+                    self.particles.push(Particle::new(x, y, &mut self.fx_rng));
+                }
+                let color = power_up.color();
+                self.trigger_flash(Color::new(color.r, color.g, color.b, 0.4));
+                self.audio.play(Sfx::PowerUp);
+                // Shield and slow time stay on the HUD while active; the
+                // others act at once, so show them briefly
+                if matches!(power_up, PowerUp::Bomb | PowerUp::ExtraLife) {
+                    self.power_up_notice = Some((power_up, POWER_UP_NOTICE_TIME));
+                }
+            }
+            GameEvent::ShieldAbsorbed => {
+                self.screen_shake = 2.0;
+                self.trigger_flash(PowerUp::Shield.color());
+            }
+            GameEvent::BombDetonated { ref targets } => {
+                for &(x, y) in targets {
+                    for _ in 0..15 {
+                        self.particles.push(Particle::new(x, y, &mut self.fx_rng));
+                    }
+                }
+                self.screen_shake = 8.0;
+                self.trigger_flash(Color::from_rgba(255, 90, 40, 150));
+                self.audio.play(Sfx::BossDefeated);
+            }
+            GameEvent::WordProgress { x, y } => {
+                for _ in 0..4 {
+                    self.particles.push(Particle::new(x, y, &mut self.fx_rng));
+                }
+                self.audio.play(Sfx::Hit);
+            }
+            GameEvent::WordDestroyed { x, y, .. } => {
+                // Bigger explosion for a whole word
+                for _ in 0..30 {
+                    self.particles.push(Particle::new(x, y, &mut self.fx_rng));
+                }
+                self.screen_shake = 4.0;
 
-            // Screen shake
-            self.screen_shake = 2.0;
-
-            // Flash green
-            self.trigger_flash(Color::from_rgba(57, 255, 20, 80));
-        } else {
-            // Wrong letter!
-            self.combo = 0;
-            self.score = (self.score - 2).max(0);
-            *self.errors.entry(typed_char).or_insert(0) += 1;
-
-            // Flash red
-            self.trigger_flash(Color::from_rgba(255, 51, 102, 150));
+                // Flash gold
+                self.trigger_flash(Color::from_rgba(255, 215, 0, 100));
+                self.audio.play(Sfx::WordComplete);
+            }
+            GameEvent::WrongKey { .. } => {
+                // Flash red
+                self.trigger_flash(Color::from_rgba(255, 51, 102, 150));
+                self.audio.play(Sfx::WrongKey);
+            }
+            GameEvent::LetterMissed { .. } | GameEvent::WordMissed { .. } => {
+                self.trigger_flash(Color::new(1.0, 0.2, 0.4, 0.4)); // Red flash
+            }
+            GameEvent::LifeLost { .. } => self.audio.play(Sfx::LifeLost),
+            GameEvent::ComboMilestone { .. } => self.audio.play(Sfx::Combo),
+            GameEvent::LevelComplete => {
+                self.audio.play(Sfx::LevelComplete);
+                self.state = GameState::LevelComplete;
+                self.finish_run();
+            }
+            GameEvent::GameOver => {
+                self.audio.play(Sfx::GameOver);
+                self.state = GameState::GameOver;
+                self.finish_run();
+            }
+            GameEvent::BossAppeared { ref name } => {
+                self.boss_banner = Some((format!("WARNING: {}", name.to_uppercase()), BOSS_BANNER_TIME));
+                self.screen_shake = 3.0;
+                self.audio.play(Sfx::BossWarning);
+            }
+            GameEvent::BossHit { x, y, .. } => {
+                for _ in 0..6 {
+                    self.particles.push(Particle::new(x, y, &mut self.fx_rng));
+                }
+                self.audio.play(Sfx::Hit);
+            }
+            GameEvent::BossPhaseChanged { phase } => {
+                self.boss_banner = Some((format!("PHASE {}", phase + 1), BOSS_BANNER_TIME));
+                self.screen_shake = 4.0;
+                self.trigger_flash(Color::from_rgba(255, 0, 110, 100));
+                self.audio.play(Sfx::Combo);
+            }
+            GameEvent::BossShotHit { .. } => {
+                self.screen_shake = 5.0;
+                self.trigger_flash(Color::new(1.0, 0.2, 0.4, 0.4));
+            }
+            GameEvent::BossDefeated { x, y, .. } => {
+                for _ in 0..80 {
+                    self.particles.push(Particle::new(x, y, &mut self.fx_rng));
+                }
+                self.screen_shake = 8.0;
+                self.trigger_flash(Color::from_rgba(255, 215, 0, 150));
+                self.audio.play(Sfx::BossDefeated);
+            }
+            GameEvent::LetterSpawned { .. } | GameEvent::WordSpawned { .. } | GameEvent::BossFired { .. } => {}
         }
     }
 
-    fn spawn_letter(&mut self) {
-        if self.level.letters.is_empty() {
+    /// Records the finished run in the profile and saves it.
+    fn finish_run(&mut self) {
+        if self.sim.is_playback() {
             return;
         }
 
-        let idx = gen_range(0, self.level.letters.len());
-        let character = self.level.letters[idx];
+        let run = self.sim.summary();
+        let level_number = match &self.mode {
+            // Practice never ends on its own and earns no bests or unlocks
+            Mode::Campaign if self.sim.mode() == RunMode::Practice => {
+                self.profile.record_play(&run, self.sim.key_analytics());
+                0
+            }
+            // Time attack keeps the fastest clear per level
+            Mode::Campaign if matches!(self.sim.mode(), RunMode::TimeAttack { .. }) => {
+                self.new_best_time = self.profile.record_time_attack(
+                    &run,
+                    self.sim.cleared(),
+                    self.sim.splits(),
+                    self.sim.key_analytics(),
+                );
+                self.current_level + 1
+            }
+            // Sudden death keeps its own leaderboard instead of the bests
+            Mode::Campaign if matches!(self.sim.mode(), RunMode::SuddenDeath { .. }) => {
+                let ground_miss = self.sim.mode() == RunMode::SuddenDeath { ground_miss: true };
+                self.last_rank = self.profile.record_sudden_death(today(), &run, ground_miss, self.sim.key_analytics());
+                self.current_level + 1
+            }
+            Mode::Campaign => {
+                self.profile.record_run(&run, self.sim.key_analytics());
+                if Profile::unlocks_next(&run) {
+                    if let Some(next) = self.pack.levels.get(self.current_level + 1) {
+                        self.profile.unlock(&next.id);
+                    }
+                }
+                self.current_level + 1
+            }
+            Mode::Daily(challenge) => {
+                self.profile.record_daily(challenge.day, &run, self.sim.key_analytics());
+                0
+            }
+        };
+
+        let mut unlocked = self.profile.achievements.observe_run(&run, level_number);
+        unlocked.extend(self.profile.achievements.observe_day(today()));
+        self.show_unlocks(&unlocked);
 
-        let screen_width = screen_width();
-        let margin = 60.0;
-        let x = gen_range(margin, screen_width - margin);
+        if let Err(err) = self.profile.save() {
+            warn!("Could not save profile: {}", err);
+        }
+    }
 
-        let letter = Letter::new(character, x, self.level.fall_speed);
-        self.letters.push(letter);
+    fn show_unlocks(&mut self, unlocked: &[&Achievement]) {
+        for achievement in unlocked {
+            self.toasts.push(Toast {
+                title: format!("Achievement: {}", achievement.name),
+                text: achievement.description.to_string(),
+                time_left: TOAST_TIME,
+            });
+        }
     }
 
     fn trigger_flash(&mut self, color: Color) {
@@ -237,16 +633,62 @@ impl Game {
         self.flash_color = color;
     }
 
+    /// Freezes the run; the simulation is simply not stepped while paused.
+    fn pause(&mut self) {
+        self.state = GameState::Paused;
+        self.pause_selection = 0;
+    }
+
+    fn update_paused(&mut self) {
+        if is_key_pressed(KeyCode::Escape) {
+            self.state = GameState::Playing;
+            return;
+        }
+        if is_key_pressed(KeyCode::Up) {
+            self.pause_selection = (self.pause_selection + PAUSE_OPTIONS.len() - 1) % PAUSE_OPTIONS.len();
+        } else if is_key_pressed(KeyCode::Down) {
+            self.pause_selection = (self.pause_selection + 1) % PAUSE_OPTIONS.len();
+        } else if is_key_pressed(KeyCode::Left) || is_key_pressed(KeyCode::Right) {
+            let step = if is_key_pressed(KeyCode::Left) { -VOLUME_STEP } else { VOLUME_STEP };
+            let mut settings = self.audio.settings();
+            match self.pause_selection {
+                2 => settings.sfx_volume = (settings.sfx_volume + step).clamp(0.0, 1.0),
+                3 => settings.music_volume = (settings.music_volume + step).clamp(0.0, 1.0),
+                _ => return,
+            }
+            self.set_audio(settings);
+        } else if is_key_pressed(KeyCode::Enter) || is_key_pressed(KeyCode::Space) {
+            match self.pause_selection {
+                0 => self.state = GameState::Playing,
+                1 => self.start_game(),
+                2 | 3 => {}
+                4 => {
+                    let settings = self.audio.settings();
+                    self.set_audio(AudioSettings { muted: !settings.muted, ..settings });
+                }
+                // Quitting is how a practice session ends; its results
+                // come up like any finished run
+                _ if self.sim.mode() == RunMode::Practice => {
+                    self.finish_run();
+                    self.state = GameState::GameOver;
+                }
+                _ => self.state = GameState::Menu,
+            }
+        }
+    }
+
     fn update_level_complete(&mut self) {
         if is_key_pressed(KeyCode::Space) || is_key_pressed(KeyCode::Enter) {
-            self.current_level += 1;
-            if self.current_level >= Level::total_levels() {
-                self.current_level = Level::total_levels() - 1;
+            // Without the unlock the player retries the same level; the
+            // daily challenge is always retried
+            if matches!(self.mode, Mode::Campaign) && self.next_level_unlocked() {
+                self.current_level += 1;
             }
-            self.level = Level::get_level(self.current_level);
-            self.start_game();
+            self.show_briefing();
         } else if is_key_pressed(KeyCode::M) {
             self.state = GameState::Menu;
+        } else if is_key_pressed(KeyCode::S) {
+            self.save_replay();
         }
     }
 
@@ -255,31 +697,39 @@ impl Game {
             self.start_game();
         } else if is_key_pressed(KeyCode::M) {
             self.state = GameState::Menu;
+        } else if is_key_pressed(KeyCode::S) {
+            self.save_replay();
         }
     }
 
-    pub fn draw(&self) {
-        // Apply screen shake
-        let shake_x = if self.screen_shake > 0.0 {
-            gen_range(-0.5, 0.5) * self.screen_shake * 4.0
-        } else {
-            0.0
-        };
-        let shake_y = if self.screen_shake > 0.0 {
-            gen_range(-0.5, 0.5) * self.screen_shake * 4.0
-        } else {
-            0.0
-        };
+    #[cfg(not(target_arch = "wasm32"))]
+    fn save_replay(&mut self) {
+        let replay = self.sim.replay();
+        let path = format!("replay-{}-{}.json", replay.level_id, replay.seed);
+        self.replay_status = Some(match std::fs::write(&path, replay.to_json()) {
+            Ok(()) => format!("Replay saved to {}", path),
+            Err(err) => format!("Could not save replay: {}", err),
+        });
+    }
 
-        let camera = Camera2D {
-            offset: vec2(shake_x, shake_y),
-            ..Default::default()
-        };
+    #[cfg(target_arch = "wasm32")]
+    fn save_replay(&mut self) {
+        self.replay_status = Some("Saving replays is not available in the browser".to_string());
+    }
+
+    pub fn draw(&self) {
+        // Draw the virtual play field letterboxed into the window, with shake
+        let camera = Layout::current().camera(self.shake_offset);
         set_camera(&camera);
+        draw_rectangle(0.0, 0.0, VIRTUAL_WIDTH, VIRTUAL_HEIGHT, Color::from_rgba(10, 14, 39, 255));
 
         match self.state {
             GameState::Menu => self.draw_menu(),
+            GameState::LevelSelect => self.draw_level_select(),
+            GameState::Achievements => self.draw_achievements(),
+            GameState::LevelBriefing => self.draw_briefing(),
             GameState::Playing => self.draw_playing(),
+            GameState::Paused => self.draw_paused(),
             GameState::LevelComplete => self.draw_level_complete(),
             GameState::GameOver => self.draw_game_over(),
         }
@@ -293,15 +743,16 @@ impl Game {
                 self.flash_color.b,
                 alpha,
             );
-            draw_rectangle(0.0, 0.0, screen_width(), screen_height(), color);
+            draw_rectangle(0.0, 0.0, VIRTUAL_WIDTH, VIRTUAL_HEIGHT, color);
         }
 
+        self.draw_toasts();
         set_default_camera();
     }
 
     fn draw_menu(&self) {
-        let width = screen_width();
-        let height = screen_height();
+        let width = VIRTUAL_WIDTH;
+        let height = VIRTUAL_HEIGHT;
 
         // Title
         let title = "TYPE STRIKE";
@@ -317,16 +768,215 @@ impl Game {
         let start_text = "Press SPACE to Start";
         draw_text(start_text, width / 2.0 - 120.0, height / 2.0 + 50.0, 25.0, WHITE);
 
+        // Level the next run starts on, with the saved best
+        let level = self.pack.get_level(self.current_level);
+        let level_text = match self.profile.record(&level.id) {
+            Some(record) => format!("{}  (best {})", level.name, record.best_score),
+            None => level.name.clone(),
+        };
+        draw_text(&level_text, width / 2.0 - 150.0, height / 2.0 + 10.0, 22.0, WHITE);
+
         // Controls
         let controls = "Type the falling letters to destroy them!";
-        draw_text(controls, width / 2.0 - 180.0, height / 2.0 + 120.0, 20.0, GRAY);
+        draw_text(controls, width / 2.0 - 180.0, height / 2.0 + 85.0, 20.0, GRAY);
+
+        // Other screens on the left, settings on the right
+        let on_off = |on: bool| if on { "ON" } else { "OFF" };
+        let screens = [
+            "L  Level select".to_string(),
+            format!("D  Daily challenge (streak {})", self.profile.daily_streak(today())),
+            "T  Achievements".to_string(),
+        ];
+        let settings = [
+            format!("P  Mode: {}", self.run_mode.name()),
+            format!("A  Adaptive difficulty: {}", on_off(matches!(self.spawner, SpawnerConfig::Adaptive { .. }))),
+            format!("K  Keyboard: {}", self.profile.layout.name()),
+            format!("N  Sound: {}", on_off(!self.audio.settings().muted)),
+        ];
+        for (i, text) in screens.iter().enumerate() {
+            draw_text(text, width / 2.0 - 340.0, height / 2.0 + 130.0 + i as f32 * 25.0, 20.0, GOLD);
+        }
+        for (i, text) in settings.iter().enumerate() {
+            draw_text(text, width / 2.0 + 40.0, height / 2.0 + 130.0 + i as f32 * 25.0, 20.0, SKYBLUE);
+        }
+    }
+
+    fn draw_level_select(&self) {
+        let width = VIRTUAL_WIDTH;
+        let height = VIRTUAL_HEIGHT;
+        let left = width / 2.0 - 300.0;
+        let row_height = 40.0;
+
+        draw_text("SELECT LEVEL", left, 70.0, 45.0, Color::from_rgba(0, 240, 255, 255));
+
+        // Scroll so the highlighted row stays in view
+        let levels = &self.pack.levels;
+        let first = self
+            .select_index
+            .saturating_sub(LEVEL_SELECT_ROWS / 2)
+            .min(levels.len().saturating_sub(LEVEL_SELECT_ROWS));
+        let visible = levels.iter().enumerate().skip(first).take(LEVEL_SELECT_ROWS);
+        for (row, (i, level)) in visible.enumerate() {
+            let y = 120.0 + row as f32 * row_height;
+            let selected = i == self.select_index;
+            if selected {
+                draw_rectangle(left - 10.0, y - 27.0, 620.0, row_height - 4.0,
+                              Color::from_rgba(30, 34, 60, 255));
+            }
+
+            let unlocked = self.profile.is_unlocked(&level.id);
+            let color = match (unlocked, selected) {
+                (false, _) => DARKGRAY,
+                (true, true) => YELLOW,
+                (true, false) => WHITE,
+            };
+            draw_text(&format!("{}  {}", level.id, level.name), left, y, 25.0, color);
+
+            if !unlocked {
+                draw_text("LOCKED", left + 480.0, y, 22.0, DARKGRAY);
+                continue;
+            }
+            if let Some(record) = self.profile.record(&level.id) {
+                let stars = record.stars() as usize;
+                let star_text = format!("{}{}", "*".repeat(stars), "-".repeat(3 - stars));
+                draw_text(&star_text, left + 380.0, y, 25.0, GOLD);
+                draw_text(&record.best_score.to_string(), left + 480.0, y, 22.0, SKYBLUE);
+            }
+        }
+
+        let level = &levels[self.select_index];
+        let detail_y = 120.0 + LEVEL_SELECT_ROWS as f32 * row_height;
+        draw_text(&level.description, left, detail_y, 22.0, WHITE);
+        if !self.profile.is_unlocked(&level.id) {
+            let needed = format!(
+                "Clear the previous level with {}% accuracy to unlock",
+                Profile::UNLOCK_ACCURACY
+            );
+            draw_text(&needed, left, detail_y + 28.0, 18.0, GRAY);
+        }
+
+        draw_text("UP/DOWN to choose, ENTER to play, ESC for menu",
+                  width / 2.0 - 220.0, height - 30.0, 18.0, GRAY);
+    }
+
+    fn draw_achievements(&self) {
+        let left = VIRTUAL_WIDTH / 2.0 - 330.0;
+        let tracker = &self.profile.achievements;
+
+        draw_text("ACHIEVEMENTS", left, 55.0, 45.0, Color::from_rgba(0, 240, 255, 255));
+
+        for (i, achievement) in ACHIEVEMENTS.iter().enumerate() {
+            let y = 95.0 + i as f32 * 31.0;
+            let unlocked = tracker.is_unlocked(achievement.id);
+            let color = if unlocked { GOLD } else { GRAY };
+            draw_text(achievement.name, left, y, 22.0, color);
+            draw_text(achievement.description, left + 200.0, y, 18.0, if unlocked { WHITE } else { GRAY });
+
+            // Progress bar for anything not yet unlocked
+            let bar_x = left + 540.0;
+            if unlocked {
+                draw_text("DONE", bar_x, y, 20.0, GREEN);
+            } else {
+                let target = achievement.condition.target();
+                let fraction = tracker.progress(achievement) as f32 / target as f32;
+                draw_rectangle(bar_x, y - 12.0, 120.0, 10.0, Color::from_rgba(30, 34, 60, 255));
+                draw_rectangle(bar_x, y - 12.0, 120.0 * fraction, 10.0, SKYBLUE);
+            }
+        }
+
+        draw_text("ESC for menu", VIRTUAL_WIDTH / 2.0 - 60.0, VIRTUAL_HEIGHT - 15.0, 18.0, GRAY);
+    }
+
+    /// Stacks unlock toasts at the top of the screen, fading them out.
+    fn draw_toasts(&self) {
+        for (i, toast) in self.toasts.iter().enumerate() {
+            let alpha = toast.time_left.min(0.5) * 2.0;
+            let x = VIRTUAL_WIDTH / 2.0 - 180.0;
+            let y = 190.0 + i as f32 * 64.0;
+            draw_rectangle(x, y, 360.0, 56.0, Color::new(0.05, 0.07, 0.18, 0.9 * alpha));
+            draw_rectangle_lines(x, y, 360.0, 56.0, 2.0, Color::new(1.0, 0.84, 0.0, alpha));
+            draw_text(&toast.title, x + 12.0, y + 24.0, 22.0, Color::new(1.0, 0.84, 0.0, alpha));
+            draw_text(&toast.text, x + 12.0, y + 46.0, 18.0, Color::new(1.0, 1.0, 1.0, alpha));
+        }
+    }
+
+    fn draw_briefing(&self) {
+        let width = VIRTUAL_WIDTH;
+        let height = VIRTUAL_HEIGHT;
+        let level = self.level();
+
+        draw_text(&format!("Level {}", level.id), width / 2.0 - 250.0, 70.0, 25.0, GRAY);
+        draw_text(&level.name, width / 2.0 - 250.0, 115.0, 45.0,
+                  Color::from_rgba(0, 240, 255, 255));
+        draw_text(&level.description, width / 2.0 - 250.0, 155.0, 22.0, WHITE);
+        if !level.hint.is_empty() {
+            draw_text(&format!("Hint: {}", level.hint), width / 2.0 - 250.0, 185.0, 18.0, SKYBLUE);
+        }
+
+        // Which finger reaches each new key on the player's layout
+        let new_letters = match self.mode {
+            Mode::Campaign => self.pack.new_letters(self.current_level),
+            Mode::Daily(_) => level.letters.clone(),
+        };
+        let fingers: Vec<String> = new_letters
+            .iter()
+            .filter_map(|&c| {
+                let position = self.profile.layout.position(c)?;
+                Some(format!("{}: {}", c, position.describe()))
+            })
+            .collect();
+        draw_text(&fingers.join("   "), width / 2.0 - 250.0, 212.0, 18.0, WHITE);
+
+        self.draw_keyboard(width / 2.0, 235.0, &level.letters, &new_letters);
+
+        match self.countdown {
+            Some(remaining) => {
+                let count = format!("{}", remaining.ceil() as i32);
+                draw_text(&count, width / 2.0 - 20.0, height - 60.0, 80.0, YELLOW);
+            }
+            None => {
+                draw_text("Press SPACE to begin", width / 2.0 - 120.0, height - 70.0, 25.0, WHITE);
+                draw_text("Press M for menu", width / 2.0 - 100.0, height - 40.0, 20.0, GRAY);
+            }
+        }
+    }
+
+    /// Draws the on-screen keyboard centred on `center_x`, highlighting the
+    /// level's keys and, more strongly, the keys it introduces.
+    fn draw_keyboard(&self, center_x: f32, top: f32, letters: &[char], new_letters: &[char]) {
+        let key_size = 40.0;
+        let gap = 5.0;
+        let rows = self.profile.layout.rows();
+        let widest = (0..rows.len())
+            .map(|row| ROW_STAGGER[row] + rows[row].chars().count() as f32)
+            .fold(0.0, f32::max);
+        let row_width = widest * (key_size + gap) - gap;
+        let same_key = |a: char, b: char| a.to_lowercase().eq(b.to_lowercase());
+
+        for (row, keys) in rows.iter().enumerate() {
+            let y = top + row as f32 * (key_size + gap);
+            let stagger = ROW_STAGGER[row];
+            for (col, key) in keys.chars().enumerate() {
+                let x = center_x - row_width / 2.0 + (stagger + col as f32) * (key_size + gap);
+                let (fill, text_color) = if new_letters.iter().any(|&c| same_key(c, key)) {
+                    (Color::from_rgba(255, 0, 110, 255), WHITE)
+                } else if letters.iter().any(|&c| same_key(c, key)) {
+                    (Color::from_rgba(0, 100, 120, 255), WHITE)
+                } else {
+                    (Color::from_rgba(30, 34, 60, 255), GRAY)
+                };
+                draw_rectangle(x, y, key_size, key_size, fill);
+                let label: String = key.to_uppercase().collect();
+                draw_text(&label, x + 13.0, y + 27.0, 24.0, text_color);
+            }
+        }
     }
 
     fn draw_playing(&self) {
         // Draw starfield background (simple)
         for i in 0..50 {
-            let x = (i * 127 % screen_width() as i32) as f32;
-            let y = (i * 211 % screen_height() as i32) as f32;
+            let x = (i * 127 % VIRTUAL_WIDTH as i32) as f32;
+            let y = (i * 211 % VIRTUAL_HEIGHT as i32) as f32;
             let size = 1.0 + (i % 3) as f32;
             draw_circle(x, y, size, Color::from_rgba(255, 255, 255, 100 + (i % 155) as u8));
         }
@@ -337,35 +987,80 @@ impl Game {
         }
 
         // Draw letters
-        for letter in &self.letters {
+        for letter in self.sim.letters() {
             letter.draw();
         }
+        for word in self.sim.words() {
+            word.draw();
+        }
+        if let Some(boss) = self.sim.boss() {
+            boss.draw();
+        }
+        for shot in self.sim.boss_shots() {
+            shot.draw();
+        }
+
+        // Draw player, inside a bubble while shielded
+        let player = self.sim.player();
+        player.draw();
+        if self.sim.has_shield() {
+            draw_circle_lines(player.x, player.y - 10.0, 45.0, 3.0, PowerUp::Shield.color());
+        }
 
-        // Draw player
-        self.player.draw();
+        // Tint the field while slow time lasts
+        if self.sim.slow_time() > 0.0 {
+            draw_rectangle(0.0, 0.0, VIRTUAL_WIDTH, VIRTUAL_HEIGHT, Color::new(0.78, 0.47, 1.0, 0.08));
+        }
 
         // Draw ground line
-        draw_line(0.0, 580.0, screen_width(), 580.0, 3.0, RED);
+        let ground_y = self.sim.ground_y();
+        draw_line(0.0, ground_y, VIRTUAL_WIDTH, ground_y, 3.0, RED);
 
         // Draw HUD
         self.draw_hud();
+
+        if let Some((text, time_left)) = &self.boss_banner {
+            let alpha = (time_left / BOSS_BANNER_TIME * 2.0).min(1.0);
+            let dims = measure_text(text, None, 50, 1.0);
+            let y = VIRTUAL_HEIGHT / 2.0 + 40.0;
+            draw_text(text, VIRTUAL_WIDTH / 2.0 - dims.width / 2.0, y, 50.0, Color::new(1.0, 0.0, 0.43, alpha));
+        }
     }
 
     fn draw_hud(&self) {
         let margin = 20.0;
 
-        // Lives
-        let lives_text = format!("Lives: {}", self.player.lives);
-        draw_text(&lives_text, margin, margin + 20.0, 25.0, RED);
+        // Lives, which only the normal rules use
+        match self.sim.mode() {
+            RunMode::Normal => {
+                let lives_text = format!("Lives: {}", self.sim.player().lives);
+                draw_text(&lives_text, margin, margin + 20.0, 25.0, RED);
+            }
+            RunMode::Practice => {
+                draw_text("PRACTICE", margin, margin + 20.0, 25.0, SKYBLUE);
+            }
+            RunMode::SuddenDeath { ground_miss } => {
+                let spare = if ground_miss && self.sim.misses() == 0 { "1 miss left" } else { "no misses" };
+                draw_text(&format!("SUDDEN DEATH - {}", spare), margin, margin + 20.0, 25.0, RED);
+            }
+            RunMode::TimeAttack { targets } => {
+                let text = format!("{}/{}  {}", self.sim.cleared(), targets, format_time(self.sim.attack_time()));
+                draw_text(&text, margin, margin + 20.0, 25.0, ORANGE);
+            }
+        }
 
         // Score
-        let score_text = format!("Score: {}", self.score);
+        let score_text = format!("Score: {}", self.sim.score());
         draw_text(&score_text, margin, margin + 50.0, 25.0, YELLOW);
 
+        if self.sim.is_playback() {
+            draw_text("REPLAY", VIRTUAL_WIDTH / 2.0 - 45.0, margin + 20.0, 30.0, ORANGE);
+        }
+
         // Combo
-        if self.combo > 0 {
-            let combo_text = format!("Combo: {}x", self.combo);
-            let combo_color = if self.combo >= 10 {
+        if self.sim.combo() > 0 {
+            let combo_text = format!("Combo: {}x", self.sim.combo());
+            let combo_color = if self.sim.combo() >= 10 {
                 Color::from_rgba(57, 255, 20, 255)
             } else {
                 WHITE
@@ -373,76 +1068,378 @@ impl Game {
             draw_text(&combo_text, margin, margin + 80.0, 25.0, combo_color);
         }
 
-        // Level info (top right)
-        let level_text = format!("Level: {}", self.level.name);
-        draw_text(&level_text, screen_width() - 300.0, margin + 20.0, 20.0, SKYBLUE);
+        // Active power-ups
+        let mut x = margin;
+        if self.sim.has_shield() {
+            draw_text("SHIELD", x, margin + 110.0, 20.0, PowerUp::Shield.color());
+            x += 80.0;
+        }
+        if self.sim.slow_time() > 0.0 {
+            let color = PowerUp::SlowTime.color();
+            draw_text("SLOW", x, margin + 110.0, 20.0, color);
+            let width = 60.0 * self.sim.slow_time() / SLOW_TIME_DURATION;
+            draw_rectangle(x + 50.0, margin + 100.0, width, 8.0, color);
+            x += 120.0;
+        }
+        if let Some((power_up, time_left)) = self.power_up_notice {
+            let color = power_up.color();
+            let alpha = (time_left / POWER_UP_NOTICE_TIME * 2.0).min(1.0);
+            draw_text(power_up.label(), x, margin + 110.0, 20.0, Color::new(color.r, color.g, color.b, alpha));
+        }
 
-        // Timer
-        let time_remaining = (self.level.duration - self.level_timer).max(0.0);
-        let timer_text = format!("Time: {:.0}s", time_remaining);
-        draw_text(&timer_text, screen_width() - 300.0, margin + 50.0, 20.0, WHITE);
+        if self.sim.mode() == RunMode::Practice {
+            self.draw_practice_panel();
+            return;
+        }
 
-        // Accuracy
-        let accuracy = if self.total_count > 0 {
-            (self.correct_count as f32 / self.total_count as f32 * 100.0) as i32
-        } else {
-            100
+        // Level info (top right)
+        let level_text = format!("Level: {}", self.sim.level().name);
+        draw_text(&level_text, VIRTUAL_WIDTH - 300.0, margin + 20.0, 20.0, SKYBLUE);
+
+        // Timer, counting up when the run has no time limit
+        let timer_text = match self.sim.mode() {
+            RunMode::TimeAttack { .. } => format!("Penalty: +{:.0}s", self.sim.attack_time() - self.sim.level_timer()),
+            _ if self.sim.boss().is_some() => "Time: BOSS".to_string(),
+            _ => {
+                let time_remaining = (self.sim.level().duration - self.sim.level_timer()).max(0.0);
+                format!("Time: {:.0}s", time_remaining)
+            }
         };
+        draw_text(&timer_text, VIRTUAL_WIDTH - 300.0, margin + 50.0, 20.0, WHITE);
+
+        // Accuracy
+        let accuracy = self.sim.accuracy();
         let accuracy_text = format!("Accuracy: {}%", accuracy);
         let accuracy_color = if accuracy >= 90 { GREEN } else if accuracy >= 70 { YELLOW } else { RED };
-        draw_text(&accuracy_text, screen_width() - 300.0, margin + 80.0, 20.0, accuracy_color);
+        draw_text(&accuracy_text, VIRTUAL_WIDTH - 300.0, margin + 80.0, 20.0, accuracy_color);
+
+        // Speed
+        let wpm = self.sim.stats().net_wpm(self.sim.level_timer());
+        let wpm_text = format!("WPM: {:.0}", wpm);
+        draw_text(&wpm_text, VIRTUAL_WIDTH - 300.0, margin + 110.0, 20.0, WHITE);
+
+        // Adaptive pace relative to the level's own speed
+        if let SpawnerConfig::Adaptive { .. } = self.sim.spawner_config() {
+            let pace = self.sim.pace().fall_speed / self.sim.level().fall_speed;
+            let pace_text = format!("Pace: {:.2}x", pace);
+            draw_text(&pace_text, VIRTUAL_WIDTH - 300.0, margin + 140.0, 20.0, SKYBLUE);
+        }
+    }
+
+    /// Live stats down the right side during practice.
+    fn draw_practice_panel(&self) {
+        let x = VIRTUAL_WIDTH - 210.0;
+        let mut y = 30.0;
+        draw_rectangle(x - 10.0, 10.0, 210.0, 330.0, Color::from_rgba(0, 0, 0, 120));
+
+        let mut line = |text: &str, color: Color| {
+            draw_text(text, x, y, 20.0, color);
+            y += 24.0;
+        };
+
+        let stats = self.sim.stats();
+        let elapsed = self.sim.level_timer();
+        line(&self.sim.level().name, SKYBLUE);
+        line(&format!("Time: {}:{:02}", elapsed as u32 / 60, elapsed as u32 % 60), WHITE);
+        line(&format!("Hits: {}", self.sim.correct_count()), GREEN);
+        line(&format!("Wrong keys: {}", self.sim.total_count() - self.sim.correct_count()), ORANGE);
+        line(&format!("Misses: {}", self.sim.misses()), RED);
+        line(&format!("Accuracy: {}%", self.sim.accuracy()), WHITE);
+        line(&format!("WPM: {:.0}", stats.net_wpm(elapsed)), WHITE);
+        line(&format!("Raw WPM: {:.0}", stats.raw_wpm(elapsed)), WHITE);
+        match stats.consistency() {
+            Some(deviation) => line(&format!("Rhythm: +/-{:.0} ms", deviation), WHITE),
+            None => line("Rhythm: -", WHITE),
+        }
+
+        line("Weakest keys", SKYBLUE);
+        for (key, accuracy) in self.sim.key_analytics().least_accurate(3) {
+            line(&format!("  {}  {:.0}%", key, accuracy * 100.0), WHITE);
+        }
+        line("ESC, then Quit to end", GRAY);
+    }
+
+    /// Speed and rhythm lines for the results screens.
+    fn speed_summary(&self) -> (String, String) {
+        let stats = self.sim.stats();
+        let elapsed = self.sim.level_timer();
+        let speed = format!(
+            "WPM: {:.0}  Raw: {:.0}  KPM: {:.0}",
+            stats.net_wpm(elapsed),
+            stats.raw_wpm(elapsed),
+            stats.keystrokes_per_minute(elapsed),
+        );
+        let consistency = match stats.consistency() {
+            Some(deviation) => format!("Consistency: +/-{:.0} ms", deviation),
+            None => "Consistency: -".to_string(),
+        };
+        (speed, consistency)
+    }
+
+    /// Post-level per-key breakdown: slowest keys, least accurate keys and
+    /// the most common mix-ups.
+    fn draw_key_breakdown(&self, x: f32, y: f32) {
+        let analytics = self.sim.key_analytics();
+        let mut line_y = y;
+        let mut line = |text: &str, color: Color| {
+            draw_text(text, x, line_y, 18.0, color);
+            line_y += 20.0;
+        };
+
+        line("Slowest keys", SKYBLUE);
+        for (key, reaction) in analytics.slowest(3) {
+            line(&format!("  {}  {:.0} ms", key, reaction * 1000.0), WHITE);
+        }
+
+        line("Least accurate", SKYBLUE);
+        for (key, accuracy) in analytics.least_accurate(3) {
+            line(&format!("  {}  {:.0}%", key, accuracy * 100.0), WHITE);
+        }
+
+        let confusions = analytics.top_confusions(3);
+        if !confusions.is_empty() {
+            line("Mix-ups", SKYBLUE);
+            for (expected, typed, count) in confusions {
+                line(&format!("  {} for {}  x{}", typed, expected, count), WHITE);
+            }
+        }
+    }
+
+    fn draw_paused(&self) {
+        self.draw_playing();
+
+        // Overlay
+        draw_rectangle(0.0, 0.0, VIRTUAL_WIDTH, VIRTUAL_HEIGHT,
+                      Color::from_rgba(0, 0, 0, 180));
+
+        let width = VIRTUAL_WIDTH;
+        let height = VIRTUAL_HEIGHT;
+
+        draw_text("PAUSED", width / 2.0 - 100.0, height / 2.0 - 150.0, 60.0, SKYBLUE);
+
+        let settings = self.audio.settings();
+        for (i, option) in PAUSE_OPTIONS.iter().enumerate() {
+            let y = height / 2.0 - 80.0 + i as f32 * 40.0;
+            let label = match i {
+                2 => format!("{}: {:.0}%", option, settings.sfx_volume * 100.0),
+                3 => format!("{}: {:.0}%", option, settings.music_volume * 100.0),
+                4 => format!("{}: {}", option, if settings.muted { "OFF" } else { "ON" }),
+                _ => option.to_string(),
+            };
+            let (text, color) = if i == self.pause_selection {
+                (format!("> {}", label), YELLOW)
+            } else {
+                (format!("  {}", label), WHITE)
+            };
+            draw_text(&text, width / 2.0 - 100.0, y, 30.0, color);
+        }
+
+        draw_text("UP/DOWN to choose, LEFT/RIGHT for volume, ENTER to select, ESC to resume",
+                  width / 2.0 - 320.0, height / 2.0 + 190.0, 18.0, GRAY);
     }
 
     fn draw_level_complete(&self) {
         self.draw_playing();
 
         // Overlay
-        draw_rectangle(0.0, 0.0, screen_width(), screen_height(),
+        draw_rectangle(0.0, 0.0, VIRTUAL_WIDTH, VIRTUAL_HEIGHT,
                       Color::from_rgba(0, 0, 0, 180));
 
-        let width = screen_width();
-        let height = screen_height();
+        let width = VIRTUAL_WIDTH;
+        let height = VIRTUAL_HEIGHT;
 
         // Title
         draw_text("LEVEL COMPLETE!", width / 2.0 - 180.0, height / 2.0 - 100.0, 60.0, GREEN);
 
+        if let RunMode::TimeAttack { .. } = self.sim.mode() {
+            let (text, color) = if self.new_best_time {
+                (format!("Time: {}  NEW BEST!", format_time(self.sim.attack_time())), GOLD)
+            } else {
+                (format!("Time: {}", format_time(self.sim.attack_time())), WHITE)
+            };
+            draw_text(&text, width / 2.0 - 100.0, height / 2.0 - 55.0, 30.0, color);
+        }
+
         // Stats
-        let accuracy = if self.total_count > 0 {
-            (self.correct_count as f32 / self.total_count as f32 * 100.0) as i32
-        } else {
-            100
-        };
+        let accuracy = self.sim.accuracy();
 
-        let score_text = format!("Score: {}", self.score);
+        let score_text = format!("Score: {}", self.sim.score());
         draw_text(&score_text, width / 2.0 - 100.0, height / 2.0 - 20.0, 30.0, YELLOW);
 
         let accuracy_text = format!("Accuracy: {}%", accuracy);
         draw_text(&accuracy_text, width / 2.0 - 100.0, height / 2.0 + 20.0, 30.0, WHITE);
 
-        // Continue
-        draw_text("Press SPACE for next level", width / 2.0 - 150.0, height / 2.0 + 80.0, 25.0, SKYBLUE);
-        draw_text("Press M for menu", width / 2.0 - 100.0, height / 2.0 + 110.0, 20.0, GRAY);
+        let (speed_text, consistency_text) = self.speed_summary();
+        draw_text(&speed_text, width / 2.0 - 100.0, height / 2.0 + 50.0, 22.0, SKYBLUE);
+        draw_text(&consistency_text, width / 2.0 - 100.0, height / 2.0 + 75.0, 22.0, SKYBLUE);
+
+        let seed_text = format!("Seed: {}", self.sim.seed());
+        draw_text(&seed_text, width / 2.0 - 100.0, height / 2.0 + 100.0, 18.0, GRAY);
+
+        // Continue, or retry when the accuracy fell short of the unlock
+        if let Mode::Daily(challenge) = &self.mode {
+            self.draw_daily_summary(challenge, height / 2.0 - 55.0);
+            draw_text("Press SPACE to try again", width / 2.0 - 150.0, height / 2.0 + 135.0, 25.0, SKYBLUE);
+        } else if self.next_level_unlocked() {
+            draw_text("Press SPACE for next level", width / 2.0 - 150.0, height / 2.0 + 135.0, 25.0, SKYBLUE);
+        } else {
+            let retry = format!("{}% accuracy unlocks the next level - SPACE to retry", Profile::UNLOCK_ACCURACY);
+            draw_text(&retry, width / 2.0 - 230.0, height / 2.0 + 135.0, 20.0, ORANGE);
+        }
+        draw_text("Press M for menu", width / 2.0 - 100.0, height / 2.0 + 165.0, 20.0, GRAY);
+        self.draw_replay_prompt(height / 2.0 + 190.0);
+        self.draw_key_breakdown(20.0, height / 2.0 - 20.0);
+        match self.sim.mode() {
+            RunMode::SuddenDeath { ground_miss } => {
+                self.draw_leaderboard(width - 190.0, height / 2.0 - 20.0, ground_miss)
+            }
+            RunMode::TimeAttack { .. } => self.draw_splits(width - 190.0, height / 2.0 - 100.0),
+            _ => {}
+        }
     }
 
     fn draw_game_over(&self) {
         self.draw_playing();
 
         // Overlay
-        draw_rectangle(0.0, 0.0, screen_width(), screen_height(),
+        draw_rectangle(0.0, 0.0, VIRTUAL_WIDTH, VIRTUAL_HEIGHT,
                       Color::from_rgba(0, 0, 0, 200));
 
-        let width = screen_width();
-        let height = screen_height();
+        let width = VIRTUAL_WIDTH;
+        let height = VIRTUAL_HEIGHT;
 
         // Title
-        draw_text("GAME OVER", width / 2.0 - 150.0, height / 2.0 - 80.0, 60.0, RED);
+        let practice = self.sim.mode() == RunMode::Practice;
+        if self.sim.playback_ended() {
+            draw_text("REPLAY ENDED", width / 2.0 - 185.0, height / 2.0 - 80.0, 60.0, ORANGE);
+        } else if practice {
+            draw_text("SESSION OVER", width / 2.0 - 190.0, height / 2.0 - 80.0, 60.0, SKYBLUE);
+        } else {
+            draw_text("GAME OVER", width / 2.0 - 150.0, height / 2.0 - 80.0, 60.0, RED);
+        }
+
+        if let Mode::Daily(challenge) = &self.mode {
+            self.draw_daily_summary(challenge, height / 2.0 - 40.0);
+        }
 
-        // Final score
-        let score_text = format!("Final Score: {}", self.score);
+        // Final score; practice has no lives to lose, so accuracy and
+        // misses say more
+        let score_text = if practice {
+            format!("Accuracy: {}%  Misses: {}", self.sim.accuracy(), self.sim.misses())
+        } else {
+            format!("Final Score: {}", self.sim.score())
+        };
         draw_text(&score_text, width / 2.0 - 120.0, height / 2.0, 30.0, YELLOW);
 
+        let (speed_text, consistency_text) = self.speed_summary();
+        draw_text(&speed_text, width / 2.0 - 120.0, height / 2.0 + 30.0, 22.0, SKYBLUE);
+        draw_text(&consistency_text, width / 2.0 - 120.0, height / 2.0 + 55.0, 22.0, SKYBLUE);
+
+        let seed_text = format!("Seed: {}", self.sim.seed());
+        draw_text(&seed_text, width / 2.0 - 120.0, height / 2.0 + 80.0, 18.0, GRAY);
+
         // Retry
-        draw_text("Press SPACE to retry", width / 2.0 - 120.0, height / 2.0 + 60.0, 25.0, WHITE);
-        draw_text("Press M for menu", width / 2.0 - 100.0, height / 2.0 + 90.0, 20.0, GRAY);
+        draw_text("Press SPACE to retry", width / 2.0 - 120.0, height / 2.0 + 115.0, 25.0, WHITE);
+        draw_text("Press M for menu", width / 2.0 - 100.0, height / 2.0 + 145.0, 20.0, GRAY);
+        self.draw_replay_prompt(height / 2.0 + 170.0);
+        self.draw_key_breakdown(20.0, height / 2.0 - 20.0);
+        match self.sim.mode() {
+            RunMode::SuddenDeath { ground_miss } => {
+                self.draw_leaderboard(width - 190.0, height / 2.0 - 20.0, ground_miss)
+            }
+            RunMode::TimeAttack { .. } => self.draw_splits(width - 190.0, height / 2.0 - 100.0),
+            _ => {}
+        }
+    }
+
+    /// Split times of a finished time-attack run next to the level's best.
+    fn draw_splits(&self, x: f32, y: f32) {
+        let best = self.profile.time_attack.get(&self.sim.level().id);
+        let title = match best {
+            Some(record) if !self.new_best_time => format!("Splits (best {})", format_time(record.best_time)),
+            _ => "Splits".to_string(),
+        };
+        draw_text(&title, x, y, 20.0, SKYBLUE);
+
+        let mut previous = 0.0;
+        for (i, &split) in self.sim.splits().iter().enumerate() {
+            let targets = (i as u32 + 1) * SPLIT_SIZE;
+            let text = format!("{:>3}  {}  (+{:.1})", targets, format_time(split), split - previous);
+            draw_text(&text, x, y + 20.0 + i as f32 * 17.0, 16.0, WHITE);
+            previous = split;
+        }
     }
+
+    /// The current level's leaderboard for this sudden-death variant, this
+    /// run highlighted.
+    fn draw_leaderboard(&self, x: f32, y: f32, ground_miss: bool) {
+        let Some(board) = self.profile.sudden_death_board(&self.sim.level().id, ground_miss) else {
+            return;
+        };
+        let variant = if ground_miss { "One-miss" } else { "Sudden death" };
+        let title = format!("{} top {}", variant, Leaderboard::SIZE);
+        draw_text(&title, x, y, 20.0, SKYBLUE);
+        for (i, entry) in board.entries.iter().enumerate() {
+            let color = if self.last_rank == Some(i) { YELLOW } else { WHITE };
+            let mark = if entry.completed { "*" } else { "" };
+            let text = format!("{}. {}{}  {:.0}s", i + 1, entry.score, mark, entry.duration);
+            draw_text(&text, x, y + 22.0 + i as f32 * 20.0, 18.0, color);
+        }
+        if self.last_rank.is_none() && !self.sim.is_playback() {
+            let footer_y = y + 32.0 + Leaderboard::SIZE as f32 * 20.0;
+            draw_text(&format!("Not in the top {}", Leaderboard::SIZE), x, footer_y, 18.0, GRAY);
+        }
+    }
+
+    /// Today's best and the daily streak, centred at height `y`.
+    fn draw_daily_summary(&self, challenge: &DailyChallenge, y: f32) {
+        let best = self.profile.daily_record(challenge.day).map_or(0, |record| record.best_score);
+        let text = format!(
+            "{}  best {}  streak {}",
+            challenge.date(),
+            best,
+            self.profile.daily_streak(today())
+        );
+        draw_text(&text, VIRTUAL_WIDTH / 2.0 - 150.0, y, 22.0, GOLD);
+    }
+
+    fn draw_replay_prompt(&self, y: f32) {
+        let text = match &self.replay_status {
+            Some(status) => status.as_str(),
+            None => "Press S to save replay",
+        };
+        draw_text(text, VIRTUAL_WIDTH / 2.0 - 100.0, y, 20.0, GRAY);
+    }
+}
+
+/// `m:ss.t` for a time-attack clock.
+fn format_time(seconds: f32) -> String {
+    let tenths = (seconds.max(0.0) * 10.0) as u32;
+    format!("{}:{:02}.{}", tenths / 600, tenths / 10 % 60, tenths % 10)
+}
+
+/// Today's UTC day number.
+fn today() -> i64 {
+    daily::day_of(miniquad::date::now())
+}
+
+/// Picks a fresh run seed from the wall clock.
+fn random_seed() -> u64 {
+    (miniquad::date::now() * 1000.0) as u64
+}
+
+/// Whether the page was hidden or blurred since the last call, as reported
+/// by the `wasm-focus.ts` plugin.
+#[cfg(target_arch = "wasm32")]
+fn focus_lost() -> bool {
+    extern "C" {
+        fn typestrike_focus_lost() -> u32;
+    }
+    unsafe { typestrike_focus_lost() != 0 }
+}
+
+/// Native windows give no focus event; see [`AUTO_PAUSE_GAP`].
+#[cfg(not(target_arch = "wasm32"))]
+fn focus_lost() -> bool {
+    false
 }
@@ -0,0 +1,163 @@
+use serde::{Deserialize, Serialize};
+
+/// Row index of the home row in [`KeyboardLayout::rows`].
+pub const HOME_ROW: usize = 2;
+
+/// Horizontal offset of each row in key widths, as on a staggered keyboard.
+pub const ROW_STAGGER: [f32; 4] = [0.0, 0.5, 0.75, 1.25];
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Hand {
+    Left,
+    Right,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Finger {
+    Pinky,
+    Ring,
+    Middle,
+    Index,
+}
+
+/// Where a key sits and which finger touch typing assigns to it.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct KeyPosition {
+    /// 0 is the number row, [`HOME_ROW`] the home row.
+    pub row: usize,
+    /// Key index from the left edge of the row.
+    pub column: usize,
+    pub hand: Hand,
+    pub finger: Finger,
+}
+
+impl KeyPosition {
+    /// Fingers follow columns: both index fingers cover two columns and the
+    /// right pinky everything past the ring finger.
+    fn at(row: usize, column: usize) -> Self {
+        let (hand, finger) = match column {
+            0 => (Hand::Left, Finger::Pinky),
+            1 => (Hand::Left, Finger::Ring),
+            2 => (Hand::Left, Finger::Middle),
+            3 | 4 => (Hand::Left, Finger::Index),
+            5 | 6 => (Hand::Right, Finger::Index),
+            7 => (Hand::Right, Finger::Middle),
+            8 => (Hand::Right, Finger::Ring),
+            _ => (Hand::Right, Finger::Pinky),
+        };
+        Self { row, column, hand, finger }
+    }
+
+    /// E.g. "left index".
+    pub fn describe(&self) -> String {
+        let hand = match self.hand {
+            Hand::Left => "left",
+            Hand::Right => "right",
+        };
+        let finger = match self.finger {
+            Finger::Pinky => "pinky",
+            Finger::Ring => "ring",
+            Finger::Middle => "middle",
+            Finger::Index => "index",
+        };
+        format!("{} {}", hand, finger)
+    }
+}
+
+/// Physical keyboard layout the player types on. Every layout shares the
+/// same key grid; only the characters printed on the keys differ.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum KeyboardLayout {
+    #[default]
+    Qwerty,
+    Dvorak,
+    Colemak,
+    Azerty,
+}
+
+impl KeyboardLayout {
+    pub const ALL: [Self; 4] = [Self::Qwerty, Self::Dvorak, Self::Colemak, Self::Azerty];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::Qwerty => "QWERTY",
+            Self::Dvorak => "Dvorak",
+            Self::Colemak => "Colemak",
+            Self::Azerty => "AZERTY",
+        }
+    }
+
+    /// Lowercase characters of each row, number row first, left to right.
+    /// AZERTY lists its digits although they need shift.
+    pub fn rows(self) -> [&'static str; 4] {
+        match self {
+            Self::Qwerty => ["1234567890-=", "qwertyuiop[]", "asdfghjkl;'", "zxcvbnm,./"],
+            Self::Dvorak => ["1234567890[]", "',.pyfgcrl/=", "aoeuidhtns-", ";qjkxbmwvz"],
+            Self::Colemak => ["1234567890-=", "qwfpgjluy;[]", "arstdhneio'", "zxcvbkm,./"],
+            Self::Azerty => ["1234567890)=", "azertyuiop^$", "qsdfghjklmù", "wxcvbn,;:!"],
+        }
+    }
+
+    /// Matches [`KeyboardLayout::name`], ignoring case.
+    pub fn from_name(name: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|layout| layout.name().eq_ignore_ascii_case(name))
+    }
+
+    /// The layout after this one, for cycling through a setting.
+    pub fn next(self) -> Self {
+        let index = Self::ALL.iter().position(|&layout| layout == self).unwrap_or(0);
+        Self::ALL[(index + 1) % Self::ALL.len()]
+    }
+
+    /// Position of the key producing `c`, ignoring case.
+    pub fn position(self, c: char) -> Option<KeyPosition> {
+        let c = lowercase(c);
+        self.rows().iter().enumerate().find_map(|(row, keys)| {
+            keys.chars().position(|key| key == c).map(|column| KeyPosition::at(row, column))
+        })
+    }
+
+    pub fn char_at(self, row: usize, column: usize) -> Option<char> {
+        self.rows().get(row)?.chars().nth(column)
+    }
+
+    /// Characters of the home row, left to right.
+    pub fn home_row(self) -> Vec<char> {
+        self.rows()[HOME_ROW].chars().collect()
+    }
+
+    /// Keys in a touch-typing teaching order: the home row from the
+    /// index fingers outwards, then the index stretch keys, then the top and
+    /// bottom rows in the same finger order. Letters are uppercase.
+    pub fn learning_order(self) -> Vec<char> {
+        const FINGER_ORDER: [usize; 10] = [3, 6, 2, 7, 1, 8, 0, 9, 4, 5];
+        let mut order = Vec::new();
+        for row in [HOME_ROW, 1, 3] {
+            for column in FINGER_ORDER {
+                if let Some(c) = self.char_at(row, column) {
+                    order.push(c.to_uppercase().next().unwrap_or(c));
+                }
+            }
+        }
+        order
+    }
+
+    /// The character on the key that produces `c` in `from`, keeping its
+    /// case. Characters `from` does not have are returned unchanged.
+    pub fn translate(self, c: char, from: KeyboardLayout) -> char {
+        let translated = from
+            .position(c)
+            .and_then(|pos| self.char_at(pos.row, pos.column))
+            .unwrap_or(c);
+        if c.is_uppercase() {
+            translated.to_uppercase().next().unwrap_or(translated)
+        } else {
+            translated
+        }
+    }
+}
+
+fn lowercase(c: char) -> char {
+    c.to_lowercase().next().unwrap_or(c)
+}
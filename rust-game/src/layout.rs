@@ -0,0 +1,52 @@
+use macroquad::prelude::*;
+
+/// Width of the virtual play field everything is laid out in.
+pub const VIRTUAL_WIDTH: f32 = 800.0;
+/// Height of the virtual play field everything is laid out in.
+pub const VIRTUAL_HEIGHT: f32 = 600.0;
+
+/// Where the virtual play field sits inside the window: scaled to fit and
+/// centred, with bars on the sides that do not match its aspect ratio.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Layout {
+    pub x: f32,
+    pub y: f32,
+    pub scale: f32,
+}
+
+impl Layout {
+    pub fn fit(screen_width: f32, screen_height: f32) -> Self {
+        let scale = (screen_width / VIRTUAL_WIDTH).min(screen_height / VIRTUAL_HEIGHT);
+        Self {
+            x: (screen_width - VIRTUAL_WIDTH * scale) / 2.0,
+            y: (screen_height - VIRTUAL_HEIGHT * scale) / 2.0,
+            scale,
+        }
+    }
+
+    pub fn current() -> Self {
+        Self::fit(screen_width(), screen_height())
+    }
+
+    /// Camera drawing virtual coordinates (y down) into the letterboxed
+    /// viewport, displaced by `shake` virtual pixels.
+    pub fn camera(&self, shake: Vec2) -> Camera2D {
+        // Viewports are in physical pixels from the bottom-left corner; the
+        // box is centred, so its bottom margin equals the top one
+        let dpi = screen_dpi_scale();
+        let viewport = (
+            (self.x * dpi) as i32,
+            (self.y * dpi) as i32,
+            (VIRTUAL_WIDTH * self.scale * dpi) as i32,
+            (VIRTUAL_HEIGHT * self.scale * dpi) as i32,
+        );
+
+        Camera2D {
+            target: vec2(VIRTUAL_WIDTH / 2.0, VIRTUAL_HEIGHT / 2.0),
+            zoom: vec2(2.0 / VIRTUAL_WIDTH, 2.0 / VIRTUAL_HEIGHT),
+            offset: vec2(shake.x * 2.0 / VIRTUAL_WIDTH, -shake.y * 2.0 / VIRTUAL_HEIGHT),
+            viewport: Some(viewport),
+            ..Default::default()
+        }
+    }
+}
@@ -1,104 +1,416 @@
 use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashSet};
+use std::fmt;
+use crate::entities::Behavior;
+use crate::keyboard::KeyboardLayout;
 
 #[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct Level {
     pub id: String,
     pub name: String,
+    #[serde(default)]
     pub letters: Vec<char>,
+    /// Words and phrases that fall alongside the single letters.
+    #[serde(default)]
+    pub words: Vec<String>,
     pub fall_speed: f32,
     pub spawn_rate: f32,
     pub duration: f32,
     pub description: String,
+    /// Tip shown in the briefing before the level starts.
+    #[serde(default)]
+    pub hint: String,
+    /// When false, `a` destroys `A` and vice versa.
+    #[serde(default)]
+    pub case_sensitive: bool,
+    /// Share of single letters spawned with each behavior, e.g.
+    /// `{"zigzag": 0.2}`; the rest fall normally.
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub behaviors: BTreeMap<Behavior, f32>,
+    /// Chance in `0.0..=1.0` that a spawned letter carries a power-up.
+    #[serde(default)]
+    pub power_up_rate: f32,
+    /// Boss that arrives when the clock runs out; the level is cleared once
+    /// it is destroyed.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub boss: Option<BossConfig>,
+}
+
+/// A boss ship as written in a level pack.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct BossConfig {
+    pub name: String,
+    /// Characters or words per phase, typed in order. Each character is one
+    /// point of health.
+    pub phases: Vec<Vec<String>>,
 }
 
 impl Level {
-    pub fn get_level(level_num: usize) -> Self {
-        match level_num {
-            0 => Self {
-                id: "1-1".to_string(),
-                name: "Home Row: F & J".to_string(),
-                letters: vec!['F', 'J'],
-                fall_speed: 100.0,
-                spawn_rate: 2.0,
-                duration: 30.0,
-                description: "Place your index fingers on F and J. Feel the bumps!".to_string(),
-            },
-            1 => Self {
-                id: "1-2".to_string(),
-                name: "Home Row: D & K".to_string(),
-                letters: vec!['F', 'J', 'D', 'K'],
-                fall_speed: 110.0,
-                spawn_rate: 1.8,
-                duration: 30.0,
-                description: "Add your middle fingers on D and K.".to_string(),
-            },
-            2 => Self {
-                id: "1-3".to_string(),
-                name: "Home Row: S & L".to_string(),
-                letters: vec!['F', 'J', 'D', 'K', 'S', 'L'],
-                fall_speed: 120.0,
-                spawn_rate: 1.6,
-                duration: 30.0,
-                description: "Ring fingers on S and L.".to_string(),
-            },
-            3 => Self {
-                id: "1-4".to_string(),
-                name: "Home Row: A & ;".to_string(),
-                letters: vec!['F', 'J', 'D', 'K', 'S', 'L', 'A', ';'],
-                fall_speed: 130.0,
-                spawn_rate: 1.5,
-                duration: 30.0,
-                description: "Pinkies on A and ;".to_string(),
-            },
-            4 => Self {
-                id: "1-5".to_string(),
-                name: "Full Home Row".to_string(),
-                letters: vec!['A', 'S', 'D', 'F', 'J', 'K', 'L', ';'],
-                fall_speed: 140.0,
-                spawn_rate: 1.3,
-                duration: 60.0,
-                description: "Master the home row!".to_string(),
-            },
-            5 => Self {
-                id: "2-1".to_string(),
-                name: "Upper Row: R & U".to_string(),
-                letters: vec!['A', 'S', 'D', 'F', 'J', 'K', 'L', ';', 'R', 'U'],
-                fall_speed: 150.0,
-                spawn_rate: 1.2,
-                duration: 45.0,
-                description: "Index fingers reach up to R and U.".to_string(),
-            },
-            6 => Self {
-                id: "2-2".to_string(),
-                name: "Upper Row: E & I".to_string(),
-                letters: vec!['A', 'S', 'D', 'F', 'J', 'K', 'L', ';', 'R', 'U', 'E', 'I'],
-                fall_speed: 160.0,
-                spawn_rate: 1.1,
-                duration: 45.0,
-                description: "Middle fingers to E and I.".to_string(),
-            },
-            7 => Self {
-                id: "2-3".to_string(),
-                name: "Speed Challenge".to_string(),
-                letters: vec!['A', 'S', 'D', 'F', 'J', 'K', 'L', 'E', 'I', 'R', 'U'],
-                fall_speed: 200.0,
-                spawn_rate: 0.9,
-                duration: 60.0,
-                description: "All letters you've learned - faster!".to_string(),
-            },
-            _ => Self {
-                id: "endless".to_string(),
-                name: "Endless Mode".to_string(),
-                letters: vec!['A', 'S', 'D', 'F', 'J', 'K', 'L', ';', 'E', 'I', 'R', 'U'],
-                fall_speed: 150.0 + (level_num as f32 * 10.0),
-                spawn_rate: 1.0,
-                duration: f32::INFINITY,
-                description: "Survive as long as you can!".to_string(),
-            },
+    /// Whether typing `typed` destroys a target showing `target`.
+    pub fn matches(&self, target: char, typed: char) -> bool {
+        if self.case_sensitive {
+            target == typed
+        } else {
+            target.to_lowercase().eq(typed.to_lowercase())
+        }
+    }
+
+    /// The same level for players on `to` when it was written for `from`:
+    /// each letter moves to the key in the same physical spot, so finger
+    /// progressions carry over. Letters named in the text follow; words are
+    /// real words and stay as they are.
+    pub fn remapped(&self, from: KeyboardLayout, to: KeyboardLayout) -> Self {
+        if from == to {
+            return self.clone();
+        }
+        // Keys without case, like `;`, may land on letters; show those in
+        // capitals like the rest unless case matters
+        let map = |c: char| {
+            let mapped = to.translate(c, from);
+            if self.case_sensitive || c.is_lowercase() {
+                mapped
+            } else {
+                mapped.to_uppercase().next().unwrap_or(mapped)
+            }
+        };
+        let retext = |text: &str| remap_text(text, &self.letters, map);
+        // Single keys on the boss move like letters; words stay words
+        let rekey = |entry: &String| {
+            let mut chars = entry.chars();
+            match (chars.next(), chars.next()) {
+                (Some(c), None) => map(c).to_string(),
+                _ => entry.clone(),
+            }
+        };
+        let boss = self.boss.as_ref().map(|boss| BossConfig {
+            name: boss.name.clone(),
+            phases: boss.phases.iter().map(|phase| phase.iter().map(rekey).collect()).collect(),
+        });
+        Self {
+            name: retext(&self.name),
+            letters: self.letters.iter().map(|&c| map(c)).collect(),
+            description: retext(&self.description),
+            hint: retext(&self.hint),
+            boss,
+            ..self.clone()
+        }
+    }
+
+    /// Unbounded survival level played after a pack's last level.
+    pub fn endless(level_num: usize, letters: Vec<char>) -> Self {
+        Self {
+            id: "endless".to_string(),
+            name: "Endless Mode".to_string(),
+            letters,
+            words: Vec::new(),
+            fall_speed: 150.0 + (level_num as f32 * 10.0),
+            spawn_rate: 1.0,
+            duration: f32::INFINITY,
+            description: "Survive as long as you can!".to_string(),
+            hint: "Speed keeps rising. Stay relaxed and keep your rhythm.".to_string(),
+            case_sensitive: false,
+            behaviors: BTreeMap::new(),
+            power_up_rate: 0.0,
+            boss: None,
         }
     }
+}
+
+/// Why a level pack was rejected on load.
+#[derive(Clone, Debug, PartialEq)]
+pub enum LevelPackError {
+    Parse(String),
+    NoLevels,
+    EmptyLetters { id: String },
+    EmptyWord { id: String },
+    InvalidSpawnRate { id: String },
+    InvalidFallSpeed { id: String },
+    InvalidDuration { id: String },
+    EmptyBoss { id: String },
+    InvalidBehaviors { id: String },
+    InvalidPowerUpRate { id: String },
+    DuplicateId { id: String },
+}
+
+impl fmt::Display for LevelPackError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::Parse(err) => write!(f, "invalid level pack: {}", err),
+            Self::NoLevels => write!(f, "level pack has no levels"),
+            Self::EmptyLetters { id } => write!(f, "level {} has no letters or words", id),
+            Self::EmptyWord { id } => write!(f, "level {} has an empty word", id),
+            Self::InvalidSpawnRate { id } => write!(f, "level {} needs a positive spawn_rate", id),
+            Self::InvalidFallSpeed { id } => write!(f, "level {} needs a positive fall_speed", id),
+            Self::InvalidDuration { id } => write!(f, "level {} needs a positive duration", id),
+            Self::EmptyBoss { id } => write!(f, "level {} has a boss with an empty phase", id),
+            Self::InvalidBehaviors { id } => {
+                write!(
+                    f,
+                    "level {} needs zigzag, accelerating, armored or splitter shares of at least 0 adding up to at most 1",
+                    id
+                )
+            }
+            Self::InvalidPowerUpRate { id } => write!(f, "level {} needs a power_up_rate from 0 to 1", id),
+            Self::DuplicateId { id } => write!(f, "level id {} is used more than once", id),
+        }
+    }
+}
+
+impl std::error::Error for LevelPackError {}
+
+/// An ordered curriculum of levels, loaded from a JSON file matching
+/// `levels/schema.json`.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct LevelPack {
+    pub name: String,
+    /// Layout the letters were chosen for.
+    #[serde(default)]
+    pub layout: KeyboardLayout,
+    pub levels: Vec<Level>,
+}
+
+impl LevelPack {
+    /// The curriculum shipped with the game.
+    pub fn builtin() -> Self {
+        Self::from_json(include_str!("../levels/default.json"))
+            .expect("built-in level pack is valid")
+    }
+
+    /// The pack as played on `layout`; see [`Level::remapped`].
+    pub fn remapped(&self, layout: KeyboardLayout) -> Self {
+        Self {
+            name: self.name.clone(),
+            layout,
+            levels: self.levels.iter().map(|level| level.remapped(self.layout, layout)).collect(),
+        }
+    }
+
+    /// Parses and validates a pack.
+    pub fn from_json(json: &str) -> Result<Self, LevelPackError> {
+        let pack: Self = serde_json::from_str(json)
+            .map_err(|err| LevelPackError::Parse(err.to_string()))?;
+        pack.validate()?;
+        Ok(pack)
+    }
+
+    pub fn to_json(&self) -> String {
+        serde_json::to_string_pretty(self).expect("level pack is always serializable")
+    }
+
+    pub fn validate(&self) -> Result<(), LevelPackError> {
+        if self.levels.is_empty() {
+            return Err(LevelPackError::NoLevels);
+        }
+
+        let mut ids = HashSet::new();
+        for level in &self.levels {
+            if level.letters.is_empty() && level.words.is_empty() {
+                return Err(LevelPackError::EmptyLetters { id: level.id.clone() });
+            }
+            if level.words.iter().any(|word| word.is_empty()) {
+                return Err(LevelPackError::EmptyWord { id: level.id.clone() });
+            }
+            if level.spawn_rate <= 0.0 {
+                return Err(LevelPackError::InvalidSpawnRate { id: level.id.clone() });
+            }
+            if level.fall_speed <= 0.0 {
+                return Err(LevelPackError::InvalidFallSpeed { id: level.id.clone() });
+            }
+            if level.duration <= 0.0 {
+                return Err(LevelPackError::InvalidDuration { id: level.id.clone() });
+            }
+            if let Some(boss) = &level.boss {
+                let empty = |phase: &Vec<String>| phase.is_empty() || phase.iter().any(String::is_empty);
+                if boss.phases.is_empty() || boss.phases.iter().any(empty) {
+                    return Err(LevelPackError::EmptyBoss { id: level.id.clone() });
+                }
+            }
+            // Normal is whatever share is left, so it is not listed
+            let shares = level.behaviors.values();
+            let lists_normal = level.behaviors.contains_key(&Behavior::Normal);
+            if lists_normal || shares.clone().any(|&share| share < 0.0) || shares.sum::<f32>() > 1.0 {
+                return Err(LevelPackError::InvalidBehaviors { id: level.id.clone() });
+            }
+            if !(0.0..=1.0).contains(&level.power_up_rate) {
+                return Err(LevelPackError::InvalidPowerUpRate { id: level.id.clone() });
+            }
+            if !ids.insert(level.id.as_str()) {
+                return Err(LevelPackError::DuplicateId { id: level.id.clone() });
+            }
+        }
+        Ok(())
+    }
+
+    /// Level `level_num` of the pack, or endless mode past the end.
+    pub fn get_level(&self, level_num: usize) -> Level {
+        match self.levels.get(level_num) {
+            Some(level) => level.clone(),
+            None => {
+                let letters = self.levels.last().map(|l| l.letters.clone()).unwrap_or_default();
+                Level::endless(level_num, letters)
+            }
+        }
+    }
+
+    pub fn total_levels(&self) -> usize {
+        self.levels.len()
+    }
+
+    /// Letters of `level_num` that the previous level did not have.
+    pub fn new_letters(&self, level_num: usize) -> Vec<char> {
+        let level = self.get_level(level_num);
+        let previous = match level_num.checked_sub(1) {
+            Some(prev) => self.get_level(prev).letters,
+            None => Vec::new(),
+        };
+        level.letters.into_iter().filter(|c| !previous.contains(c)).collect()
+    }
+
+    /// Index of the level with the given `id`, if it is part of the pack.
+    pub fn index_of(&self, id: &str) -> Option<usize> {
+        self.levels.iter().position(|level| level.id == id)
+    }
+}
+
+/// Replaces standalone mentions of `letters` in `text`, such as the `F` in
+/// "F & J" or "on F and J.", leaving every other word alone.
+fn remap_text(text: &str, letters: &[char], map: impl Fn(char) -> char) -> String {
+    let words: Vec<String> = text
+        .split(' ')
+        .map(|word| {
+            let mut chars = word.chars();
+            match (chars.next(), chars.next(), chars.next()) {
+                (Some(c), None, _) if letters.contains(&c) => map(c).to_string(),
+                (Some(c), Some(p), None) if letters.contains(&c) && ".,!?:".contains(p) => {
+                    format!("{}{}", map(c), p)
+                }
+                _ => word.to_string(),
+            }
+        })
+        .collect();
+    words.join(" ")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn level(id: &str) -> Level {
+        Level {
+            id: id.to_string(),
+            name: "Test".to_string(),
+            letters: vec!['f', 'j'],
+            words: Vec::new(),
+            fall_speed: 100.0,
+            spawn_rate: 2.0,
+            duration: 30.0,
+            description: String::new(),
+            hint: String::new(),
+            case_sensitive: false,
+            behaviors: BTreeMap::new(),
+            power_up_rate: 0.0,
+            boss: None,
+        }
+    }
+
+    /// Validates a pack of one level after applying `change` to it.
+    fn validate_with(change: impl FnOnce(&mut Level)) -> Result<(), LevelPackError> {
+        let mut level = level("1-1");
+        change(&mut level);
+        LevelPack { name: "Test".to_string(), layout: KeyboardLayout::Qwerty, levels: vec![level] }.validate()
+    }
+
+    #[test]
+    fn builtin_pack_is_valid() {
+        assert!(LevelPack::builtin().validate().is_ok());
+        assert_eq!(validate_with(|_| {}), Ok(()));
+    }
+
+    #[test]
+    fn rejects_unparsable_json() {
+        assert!(matches!(LevelPack::from_json("{"), Err(LevelPackError::Parse(_))));
+    }
+
+    #[test]
+    fn rejects_a_pack_without_levels() {
+        let pack = LevelPack { name: "Empty".to_string(), layout: KeyboardLayout::Qwerty, levels: Vec::new() };
+        assert_eq!(pack.validate(), Err(LevelPackError::NoLevels));
+    }
+
+    #[test]
+    fn rejects_a_level_without_letters_or_words() {
+        let result = validate_with(|level| level.letters.clear());
+        assert_eq!(result, Err(LevelPackError::EmptyLetters { id: "1-1".to_string() }));
+    }
+
+    #[test]
+    fn rejects_an_empty_word() {
+        let result = validate_with(|level| level.words = vec!["home".to_string(), String::new()]);
+        assert_eq!(result, Err(LevelPackError::EmptyWord { id: "1-1".to_string() }));
+    }
+
+    #[test]
+    fn rejects_a_non_positive_spawn_rate() {
+        let result = validate_with(|level| level.spawn_rate = 0.0);
+        assert_eq!(result, Err(LevelPackError::InvalidSpawnRate { id: "1-1".to_string() }));
+    }
+
+    #[test]
+    fn rejects_a_non_positive_fall_speed() {
+        let result = validate_with(|level| level.fall_speed = -50.0);
+        assert_eq!(result, Err(LevelPackError::InvalidFallSpeed { id: "1-1".to_string() }));
+    }
+
+    #[test]
+    fn rejects_a_non_positive_duration() {
+        let result = validate_with(|level| level.duration = 0.0);
+        assert_eq!(result, Err(LevelPackError::InvalidDuration { id: "1-1".to_string() }));
+    }
+
+    #[test]
+    fn rejects_a_boss_with_an_empty_phase() {
+        let result = validate_with(|level| {
+            level.boss = Some(BossConfig {
+                name: "Guardian".to_string(),
+                phases: vec![vec!["f".to_string()], Vec::new()],
+            })
+        });
+        assert_eq!(result, Err(LevelPackError::EmptyBoss { id: "1-1".to_string() }));
+    }
+
+    #[test]
+    fn rejects_behavior_shares_over_one() {
+        let result = validate_with(|level| {
+            level.behaviors = BTreeMap::from([(Behavior::Zigzag, 0.6), (Behavior::Armored, 0.6)])
+        });
+        assert_eq!(result, Err(LevelPackError::InvalidBehaviors { id: "1-1".to_string() }));
+    }
+
+    #[test]
+    fn rejects_a_negative_behavior_share() {
+        let result = validate_with(|level| level.behaviors = BTreeMap::from([(Behavior::Splitter, -0.1)]));
+        assert_eq!(result, Err(LevelPackError::InvalidBehaviors { id: "1-1".to_string() }));
+    }
+
+    #[test]
+    fn rejects_a_listed_normal_behavior() {
+        let result = validate_with(|level| level.behaviors = BTreeMap::from([(Behavior::Normal, 0.5)]));
+        assert_eq!(result, Err(LevelPackError::InvalidBehaviors { id: "1-1".to_string() }));
+    }
+
+    #[test]
+    fn rejects_a_power_up_rate_outside_zero_to_one() {
+        let result = validate_with(|level| level.power_up_rate = 1.5);
+        assert_eq!(result, Err(LevelPackError::InvalidPowerUpRate { id: "1-1".to_string() }));
+    }
 
-    pub fn total_levels() -> usize {
-        8
+    #[test]
+    fn rejects_duplicate_ids() {
+        let pack = LevelPack {
+            name: "Test".to_string(),
+            layout: KeyboardLayout::Qwerty,
+            levels: vec![level("1-1"), level("1-2"), level("1-1")],
+        };
+        assert_eq!(pack.validate(), Err(LevelPackError::DuplicateId { id: "1-1".to_string() }));
     }
 }
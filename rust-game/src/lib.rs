@@ -1,17 +1,73 @@
 use macroquad::prelude::*;
 
+mod audio;
 mod game;
-mod entities;
-mod levels;
+mod layout;
+pub mod achievements;
+pub mod curriculum;
+pub mod daily;
+pub mod entities;
+pub mod keyboard;
+pub mod levels;
+pub mod profile;
+pub mod replay;
+pub mod simulation;
+pub mod spawner;
+pub mod stats;
 
+use audio::Audio;
+use curriculum::Curriculum;
 use game::Game;
+use keyboard::KeyboardLayout;
+use levels::LevelPack;
+use profile::Profile;
+
+/// Loads the level pack from `TYPESTRIKE_LEVELS` or `levels.json`, falling
+/// back to the built-in curriculum when there is none or it is invalid.
+/// `TYPESTRIKE_CURRICULUM=<layout>` generates a course for that layout instead.
+async fn load_level_pack() -> LevelPack {
+    if let Ok(name) = std::env::var("TYPESTRIKE_CURRICULUM") {
+        match KeyboardLayout::from_name(&name).map(|layout| Curriculum::new(layout).generate()) {
+            Some(Ok(pack)) => return pack,
+            Some(Err(err)) => warn!("Could not generate a curriculum: {}", err),
+            None => warn!("Unknown keyboard layout {}", name),
+        }
+    }
+
+    let path = std::env::var("TYPESTRIKE_LEVELS").unwrap_or_else(|_| "levels.json".to_string());
+    match load_string(&path).await {
+        Ok(json) => match LevelPack::from_json(&json) {
+            Ok(pack) => pack,
+            Err(err) => {
+                warn!("Ignoring {}: {}", path, err);
+                LevelPack::builtin()
+            }
+        },
+        Err(_) => LevelPack::builtin(),
+    }
+}
 
 #[macroquad::main("TypeStrike")]
-async fn main() {
-    let mut game = Game::new();
+pub async fn main() {
+    let profile = Profile::load();
+    let audio = Audio::load(profile.audio).await;
+    let mut game = Game::new(load_level_pack().await, profile, audio);
+    if let Some(seed) = std::env::var("TYPESTRIKE_SEED").ok().and_then(|s| s.parse().ok()) {
+        game.set_next_seed(seed);
+    }
+    if let Ok(path) = std::env::var("TYPESTRIKE_REPLAY") {
+        match std::fs::read_to_string(&path).map(|json| replay::Replay::from_json(&json)) {
+            Ok(Ok(replay)) if replay.version != replay::Replay::VERSION => {
+                warn!("Replay {} is version {}, expected {}", path, replay.version, replay::Replay::VERSION)
+            }
+            Ok(Ok(replay)) => game.play_replay(replay),
+            Ok(Err(err)) => warn!("Invalid replay {}: {}", path, err),
+            Err(err) => warn!("Could not read replay {}: {}", path, err),
+        }
+    }
 
     loop {
-        clear_background(Color::from_rgba(10, 14, 39, 255)); // Deep space background
+        clear_background(Color::from_rgba(4, 6, 18, 255)); // Letterbox bars
 
         game.update(get_frame_time());
         game.draw();
@@ -0,0 +1,498 @@
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::{BTreeMap, BTreeSet};
+use std::fmt;
+use crate::achievements::AchievementTracker;
+use crate::daily::date_string;
+use crate::keyboard::KeyboardLayout;
+use crate::simulation::RunSummary;
+use crate::stats::KeyAnalytics;
+
+/// Best results on one level.
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct LevelRecord {
+    pub best_score: i32,
+    pub best_accuracy: i32,
+    pub best_wpm: f32,
+    pub completions: u32,
+}
+
+impl LevelRecord {
+    /// Zero to three stars: one for clearing the level, more for clearing
+    /// it at 90% and 97% accuracy.
+    pub fn stars(&self) -> u8 {
+        match (self.completions, self.best_accuracy) {
+            (0, _) => 0,
+            (_, accuracy) if accuracy >= 97 => 3,
+            (_, accuracy) if accuracy >= 90 => 2,
+            _ => 1,
+        }
+    }
+}
+
+/// Best result of one day's challenge, counting runs that ended early.
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct DailyRecord {
+    pub best_score: i32,
+    pub best_accuracy: i32,
+    pub best_wpm: f32,
+    pub attempts: u32,
+}
+
+/// One run on a [`Leaderboard`].
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct LeaderboardEntry {
+    pub score: i32,
+    pub accuracy: i32,
+    /// Seconds survived.
+    pub duration: f32,
+    pub completed: bool,
+    /// UTC day the run was played, in days since the Unix epoch.
+    pub day: i64,
+}
+
+/// The best runs on one level, highest score first.
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct Leaderboard {
+    pub entries: Vec<LeaderboardEntry>,
+}
+
+impl Leaderboard {
+    pub const SIZE: usize = 10;
+
+    /// Adds `entry` and returns its 0-based rank, or `None` if it did not
+    /// make the board. Ties go to the longer run, then the earlier one.
+    pub fn insert(&mut self, entry: LeaderboardEntry) -> Option<usize> {
+        let rank = self
+            .entries
+            .iter()
+            .position(|e| (entry.score, entry.duration) > (e.score, e.duration))
+            .unwrap_or(self.entries.len());
+        if rank >= Self::SIZE {
+            return None;
+        }
+        self.entries.insert(rank, entry);
+        self.entries.truncate(Self::SIZE);
+        Some(rank)
+    }
+}
+
+/// Fastest time-attack clear of one level.
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct TimeAttackRecord {
+    /// Seconds including penalties.
+    pub best_time: f32,
+    pub targets: u32,
+    /// Split times of the best run.
+    pub splits: Vec<f32>,
+}
+
+/// Volume per channel, each in `0.0..=1.0`.
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+pub struct AudioSettings {
+    pub sfx_volume: f32,
+    pub music_volume: f32,
+    pub muted: bool,
+}
+
+impl Default for AudioSettings {
+    fn default() -> Self {
+        Self {
+            sfx_volume: 0.8,
+            music_volume: 0.5,
+            muted: false,
+        }
+    }
+}
+
+/// Everything that survives between launches.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct Profile {
+    pub version: u32,
+    /// Ids of levels the player may start.
+    #[serde(default)]
+    pub unlocked: BTreeSet<String>,
+    /// Best results keyed by `Level.id`.
+    #[serde(default)]
+    pub records: BTreeMap<String, LevelRecord>,
+    /// Seconds spent in runs.
+    #[serde(default)]
+    pub total_play_time: f64,
+    /// Per-key stats summed over every run.
+    #[serde(default)]
+    pub key_stats: KeyAnalytics,
+    /// Layout the player types on; levels are remapped to it.
+    #[serde(default)]
+    pub layout: KeyboardLayout,
+    #[serde(default)]
+    pub audio: AudioSettings,
+    #[serde(default)]
+    pub achievements: AchievementTracker,
+    /// Daily challenge bests keyed by `YYYY-MM-DD`.
+    #[serde(default)]
+    pub daily: BTreeMap<String, DailyRecord>,
+    /// Sudden-death runs keyed by `Level.id`, apart from the normal bests.
+    #[serde(default)]
+    pub sudden_death: BTreeMap<String, Leaderboard>,
+    /// Sudden-death runs with one ground miss allowed, keyed by `Level.id`.
+    /// The easier rules get their own boards.
+    #[serde(default)]
+    pub sudden_death_one_miss: BTreeMap<String, Leaderboard>,
+    /// Fastest time-attack clears keyed by `Level.id`.
+    #[serde(default)]
+    pub time_attack: BTreeMap<String, TimeAttackRecord>,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum ProfileError {
+    /// The save exists but could not be read.
+    Io(String),
+    Parse(String),
+    MissingVersion,
+    TooNew { version: u32 },
+}
+
+impl fmt::Display for ProfileError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::Io(err) => write!(f, "could not read profile: {}", err),
+            Self::Parse(err) => write!(f, "invalid profile: {}", err),
+            Self::MissingVersion => write!(f, "profile has no version"),
+            Self::TooNew { version } => write!(
+                f,
+                "profile version {} is newer than supported version {}",
+                version,
+                Profile::VERSION
+            ),
+        }
+    }
+}
+
+impl std::error::Error for ProfileError {}
+
+impl Default for Profile {
+    fn default() -> Self {
+        Self {
+            version: Self::VERSION,
+            unlocked: BTreeSet::new(),
+            records: BTreeMap::new(),
+            total_play_time: 0.0,
+            key_stats: KeyAnalytics::new(),
+            layout: KeyboardLayout::Qwerty,
+            audio: AudioSettings::default(),
+            achievements: AchievementTracker::default(),
+            daily: BTreeMap::new(),
+            sudden_death: BTreeMap::new(),
+            sudden_death_one_miss: BTreeMap::new(),
+            time_attack: BTreeMap::new(),
+        }
+    }
+}
+
+impl Profile {
+    /// Current save format. Bump it and add a step to [`Profile::migrate`]
+    /// whenever a change cannot be covered by `#[serde(default)]`.
+    pub const VERSION: u32 = 1;
+
+    /// Accuracy a completed run needs to unlock the following level.
+    pub const UNLOCK_ACCURACY: i32 = 80;
+
+    /// Loads the saved profile, or a fresh one if there is none. A save that
+    /// cannot be read, e.g. one from a newer version, is moved aside first
+    /// so the fresh profile never overwrites it.
+    pub fn load() -> Self {
+        match storage::read() {
+            Ok(Some(json)) => Self::from_json(&json).unwrap_or_else(Self::start_over),
+            Ok(None) => Self::default(),
+            Err(err) => Self::start_over(ProfileError::Io(err)),
+        }
+    }
+
+    /// A fresh profile in place of a save that failed with `err`, which is
+    /// moved to its backup.
+    fn start_over(err: ProfileError) -> Self {
+        match storage::back_up() {
+            Ok(backup) => macroquad::logging::warn!(
+                "Starting a new profile: {}; the old one was moved to {}",
+                err,
+                backup
+            ),
+            Err(backup_err) => macroquad::logging::warn!(
+                "Starting a new profile: {}; could not move the old one aside: {}",
+                err,
+                backup_err
+            ),
+        }
+        Self::default()
+    }
+
+    pub fn save(&self) -> Result<(), String> {
+        storage::write(&self.to_json())
+    }
+
+    /// Parses a save of any supported version, migrating it to the current one.
+    pub fn from_json(json: &str) -> Result<Self, ProfileError> {
+        let value: Value = serde_json::from_str(json)
+            .map_err(|err| ProfileError::Parse(err.to_string()))?;
+        let value = Self::migrate(value)?;
+        serde_json::from_value(value).map_err(|err| ProfileError::Parse(err.to_string()))
+    }
+
+    pub fn to_json(&self) -> String {
+        serde_json::to_string_pretty(self).expect("profile is always serializable")
+    }
+
+    /// Upgrades a raw save one version at a time until it is current.
+    fn migrate(value: Value) -> Result<Value, ProfileError> {
+        let version = value
+            .get("version")
+            .and_then(Value::as_u64)
+            .ok_or(ProfileError::MissingVersion)? as u32;
+
+        if version > Self::VERSION {
+            return Err(ProfileError::TooNew { version });
+        }
+        // Version 1 is the first format, so there is nothing to upgrade yet
+        Ok(value)
+    }
+
+    pub fn is_unlocked(&self, level_id: &str) -> bool {
+        self.unlocked.contains(level_id)
+    }
+
+    pub fn unlock(&mut self, level_id: &str) {
+        self.unlocked.insert(level_id.to_string());
+    }
+
+    /// Whether `run` earns access to the level after it.
+    pub fn unlocks_next(run: &RunSummary) -> bool {
+        run.completed && run.accuracy >= Self::UNLOCK_ACCURACY
+    }
+
+    pub fn record(&self, level_id: &str) -> Option<&LevelRecord> {
+        self.records.get(level_id)
+    }
+
+    /// Folds a finished run into the bests, play time and key stats.
+    pub fn record_run(&mut self, run: &RunSummary, keys: &KeyAnalytics) {
+        self.record_play(run, keys);
+
+        let record = self.records.entry(run.level_id.clone()).or_default();
+        record.best_score = record.best_score.max(run.score);
+        if run.completed {
+            record.completions += 1;
+            record.best_accuracy = record.best_accuracy.max(run.accuracy);
+            record.best_wpm = record.best_wpm.max(run.net_wpm);
+        }
+    }
+
+    /// Folds a daily challenge run played on `day` into that day's best.
+    pub fn record_daily(&mut self, day: i64, run: &RunSummary, keys: &KeyAnalytics) {
+        self.record_play(run, keys);
+
+        let record = self.daily.entry(date_string(day)).or_default();
+        record.attempts += 1;
+        record.best_score = record.best_score.max(run.score);
+        record.best_accuracy = record.best_accuracy.max(run.accuracy);
+        record.best_wpm = record.best_wpm.max(run.net_wpm);
+    }
+
+    pub fn daily_record(&self, day: i64) -> Option<&DailyRecord> {
+        self.daily.get(&date_string(day))
+    }
+
+    /// Consecutive days with a daily challenge played, ending today, or
+    /// yesterday while today's is still open.
+    pub fn daily_streak(&self, today: i64) -> u32 {
+        let mut day = if self.daily_record(today).is_some() { today } else { today - 1 };
+        let mut streak = 0;
+        while self.daily_record(day).is_some() {
+            streak += 1;
+            day -= 1;
+        }
+        streak
+    }
+
+    /// The sudden-death leaderboard of `level_id` for the strict rules, or
+    /// for the variant allowing one ground miss.
+    pub fn sudden_death_board(&self, level_id: &str, ground_miss: bool) -> Option<&Leaderboard> {
+        let boards = if ground_miss { &self.sudden_death_one_miss } else { &self.sudden_death };
+        boards.get(level_id)
+    }
+
+    /// Enters a sudden-death run played on `day` into its level's
+    /// leaderboard for the variant, returning its rank there if it made
+    /// the board.
+    pub fn record_sudden_death(
+        &mut self,
+        day: i64,
+        run: &RunSummary,
+        ground_miss: bool,
+        keys: &KeyAnalytics,
+    ) -> Option<usize> {
+        self.record_play(run, keys);
+        let boards = if ground_miss { &mut self.sudden_death_one_miss } else { &mut self.sudden_death };
+        boards.entry(run.level_id.clone()).or_default().insert(LeaderboardEntry {
+            score: run.score,
+            accuracy: run.accuracy,
+            duration: run.duration,
+            completed: run.completed,
+            day,
+        })
+    }
+
+    /// Keeps a completed time-attack run if it beats the level's best;
+    /// returns whether it did.
+    pub fn record_time_attack(&mut self, run: &RunSummary, targets: u32, splits: &[f32], keys: &KeyAnalytics) -> bool {
+        self.record_play(run, keys);
+        if !run.completed {
+            return false;
+        }
+
+        let time = run.duration + run.penalty;
+        let best = self.time_attack.get(&run.level_id).map(|record| record.best_time);
+        if best.is_some_and(|best| best <= time) {
+            return false;
+        }
+        self.time_attack.insert(run.level_id.clone(), TimeAttackRecord {
+            best_time: time,
+            targets,
+            splits: splits.to_vec(),
+        });
+        true
+    }
+
+    /// Adds the run's play time and key stats without touching any bests,
+    /// e.g. for practice sessions.
+    pub fn record_play(&mut self, run: &RunSummary, keys: &KeyAnalytics) {
+        self.total_play_time += run.duration as f64;
+        self.key_stats.merge(keys);
+    }
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+mod storage {
+    use std::path::PathBuf;
+
+    /// `TYPESTRIKE_PROFILE` if set, otherwise `~/.typestrike/profile.json`.
+    fn path() -> PathBuf {
+        if let Ok(path) = std::env::var("TYPESTRIKE_PROFILE") {
+            return PathBuf::from(path);
+        }
+        let home = std::env::var("HOME")
+            .or_else(|_| std::env::var("USERPROFILE"))
+            .unwrap_or_else(|_| ".".to_string());
+        PathBuf::from(home).join(".typestrike").join("profile.json")
+    }
+
+    /// The save, or `None` if there is none yet.
+    pub fn read() -> Result<Option<String>, String> {
+        match std::fs::read_to_string(path()) {
+            Ok(json) => Ok(Some(json)),
+            Err(err) if err.kind() == std::io::ErrorKind::NotFound => Ok(None),
+            Err(err) => Err(err.to_string()),
+        }
+    }
+
+    /// Writes a temporary file and renames it over the save, so a crash
+    /// mid-write leaves the previous save intact.
+    pub fn write(json: &str) -> Result<(), String> {
+        let path = path();
+        if let Some(dir) = path.parent() {
+            std::fs::create_dir_all(dir).map_err(|err| err.to_string())?;
+        }
+        let temp = path.with_extension("json.tmp");
+        std::fs::write(&temp, json).map_err(|err| err.to_string())?;
+        std::fs::rename(&temp, &path).map_err(|err| err.to_string())
+    }
+
+    /// Moves the save to `profile.json.bak`, returning where it went.
+    pub fn back_up() -> Result<String, String> {
+        let path = path();
+        let backup = path.with_extension("json.bak");
+        std::fs::rename(&path, &backup).map_err(|err| err.to_string())?;
+        Ok(backup.display().to_string())
+    }
+}
+
+/// Browser `localStorage`, reached through the functions registered by the
+/// `wasm-storage.ts` miniquad plugin in the web app.
+#[cfg(target_arch = "wasm32")]
+mod storage {
+    extern "C" {
+        fn typestrike_storage_len() -> u32;
+        fn typestrike_storage_read(ptr: *mut u8, len: u32);
+        /// Returns 0 when `localStorage` refused the write, e.g. when full.
+        fn typestrike_storage_write(ptr: *const u8, len: u32) -> u32;
+        /// Returns 0 when the backup could not be stored.
+        fn typestrike_storage_backup() -> u32;
+    }
+
+    /// The save, or `None` if there is none yet.
+    pub fn read() -> Result<Option<String>, String> {
+        let len = unsafe { typestrike_storage_len() };
+        if len == 0 {
+            return Ok(None);
+        }
+        let mut buf = vec![0u8; len as usize];
+        unsafe { typestrike_storage_read(buf.as_mut_ptr(), len) };
+        String::from_utf8(buf).map(Some).map_err(|err| err.to_string())
+    }
+
+    pub fn write(json: &str) -> Result<(), String> {
+        match unsafe { typestrike_storage_write(json.as_ptr(), json.len() as u32) } {
+            0 => Err("localStorage refused the profile".to_string()),
+            _ => Ok(()),
+        }
+    }
+
+    /// Copies the save to its backup key, returning the key.
+    pub fn back_up() -> Result<String, String> {
+        match unsafe { typestrike_storage_backup() } {
+            0 => Err("localStorage refused the backup".to_string()),
+            _ => Ok("localStorage typestrike-profile.bak".to_string()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn a_saved_profile_loads_back_unchanged() {
+        let mut profile = Profile::default();
+        profile.unlock("1-2");
+        profile.total_play_time = 90.5;
+        profile.layout = KeyboardLayout::Dvorak;
+        profile.records.insert("1-1".to_string(), LevelRecord { best_score: 420, ..Default::default() });
+
+        assert_eq!(Profile::from_json(&profile.to_json()), Ok(profile));
+    }
+
+    #[test]
+    fn migrate_keeps_a_current_save_and_fills_in_missing_fields() {
+        let value = serde_json::json!({ "version": Profile::VERSION, "total_play_time": 12.0 });
+        assert_eq!(Profile::migrate(value.clone()), Ok(value));
+
+        let profile = Profile::from_json(r#"{"version": 1, "total_play_time": 12.0}"#).unwrap();
+        assert_eq!(profile.total_play_time, 12.0);
+        assert!(profile.records.is_empty());
+    }
+
+    #[test]
+    fn rejects_a_save_without_a_version() {
+        assert_eq!(Profile::from_json(r#"{"total_play_time": 12.0}"#), Err(ProfileError::MissingVersion));
+    }
+
+    #[test]
+    fn rejects_a_save_from_a_newer_version() {
+        let json = format!(r#"{{"version": {}}}"#, Profile::VERSION + 1);
+        assert_eq!(Profile::from_json(&json), Err(ProfileError::TooNew { version: Profile::VERSION + 1 }));
+    }
+
+    #[test]
+    fn rejects_a_save_that_is_not_json() {
+        assert!(matches!(Profile::from_json("{\"version\": 1,"), Err(ProfileError::Parse(_))));
+    }
+}
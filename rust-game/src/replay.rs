@@ -0,0 +1,91 @@
+use serde::{Deserialize, Serialize};
+use crate::keyboard::KeyboardLayout;
+use crate::levels::Level;
+use crate::simulation::{RunMode, RunState, Simulation};
+use crate::spawner::SpawnerConfig;
+
+/// A key press applied at a given simulation tick.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct ReplayKey {
+    pub tick: u64,
+    pub char: char,
+}
+
+/// Everything needed to reproduce a run: the seed, the level, the play-field
+/// size and every key press with the tick it landed on.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct Replay {
+    pub version: u32,
+    pub seed: u64,
+    pub level_id: String,
+    pub field_width: f32,
+    pub field_height: f32,
+    #[serde(default)]
+    pub spawner: SpawnerConfig,
+    #[serde(default)]
+    pub mode: RunMode,
+    /// Tick the recording stopped on. Practice runs never end by
+    /// themselves, so playback stops here.
+    #[serde(default)]
+    pub end_tick: Option<u64>,
+    /// Layout the level was remapped to when the run was recorded.
+    #[serde(default)]
+    pub layout: KeyboardLayout,
+    pub keys: Vec<ReplayKey>,
+}
+
+impl Replay {
+    pub const VERSION: u32 = 1;
+
+    pub fn to_json(&self) -> String {
+        serde_json::to_string_pretty(self).expect("replay is always serializable")
+    }
+
+    pub fn from_json(json: &str) -> Result<Self, serde_json::Error> {
+        serde_json::from_str(json)
+    }
+
+    /// Plays the replay headlessly against `level` until the run ends and
+    /// returns the finished simulation for inspecting score and stats.
+    pub fn simulate(&self, level: Level) -> Simulation {
+        let mut sim = Simulation::from_replay(self, level);
+        while sim.state() == RunState::Playing && !sim.playback_ended() {
+            sim.tick(&[]);
+        }
+        sim
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::levels::LevelPack;
+    use crate::simulation::InputEvent;
+
+    #[test]
+    fn a_saved_replay_reproduces_the_run() {
+        let level = LevelPack::builtin().get_level(1);
+        let mut sim = Simulation::new(level.clone(), 800.0, 600.0, 42);
+        sim.set_spawner(SpawnerConfig::Adaptive { min_accuracy: 0.9, max_accuracy: 0.95 });
+
+        // Uneven frames, some letters typed, some missed and a few wrong keys
+        for frame in 0..1500 {
+            let mut inputs = Vec::new();
+            if frame % 7 == 0 {
+                if let Some(letter) = sim.letters().iter().find(|letter| letter.is_in_view()) {
+                    inputs.push(InputEvent::Char(letter.char));
+                }
+            }
+            if frame % 97 == 0 {
+                inputs.push(InputEvent::Char('x'));
+            }
+            sim.step(if frame % 3 == 0 { 0.021 } else { 0.013 }, &inputs);
+        }
+        assert!(sim.total_count() > 0);
+
+        let replay = Replay::from_json(&sim.replay().to_json()).unwrap();
+        let replayed = replay.simulate(level);
+        assert_eq!(replayed.summary(), sim.summary());
+        assert_eq!(replayed.ticks(), sim.ticks());
+    }
+}
@@ -0,0 +1,1134 @@
+use serde::{Deserialize, Serialize};
+use rand::{Rng, SeedableRng};
+use rand_chacha::ChaCha8Rng;
+use crate::entities::{Behavior, Boss, BossProgress, BossShot, Letter, Player, PowerUp, Word};
+use crate::keyboard::KeyboardLayout;
+use crate::levels::Level;
+use crate::replay::{Replay, ReplayKey};
+use crate::spawner::{Pace, SpawnContext, SpawnStrategy, SpawnerConfig, Target};
+use crate::stats::{KeyAnalytics, TypingStats};
+
+/// Distance of the ground line above the bottom of the play field.
+const GROUND_MARGIN: f32 = 20.0;
+
+/// Horizontal spawn margin for single letters, as a share of field width.
+const SPAWN_MARGIN: f32 = 0.075;
+
+/// Combo step that raises the score multiplier.
+pub const COMBO_MILESTONE: i32 = 10;
+
+/// Most targets on screen at once in time attack.
+pub const TIME_ATTACK_CAP: usize = 5;
+
+/// Shortest gap between time-attack spawns, so targets never overlap.
+const TIME_ATTACK_GAP: f32 = 0.35;
+
+/// Seconds added to a time-attack result per wrong key.
+pub const WRONG_KEY_PENALTY: f32 = 2.0;
+
+/// Seconds added to a time-attack result per target reaching the ground.
+pub const GROUND_PENALTY: f32 = 5.0;
+
+/// Targets per split on the time-attack results.
+pub const SPLIT_SIZE: u32 = 10;
+
+/// Speed of a boss's shots in pixels per second.
+const BOSS_SHOT_SPEED: f32 = 350.0;
+
+/// Points per phase for destroying a boss, before the combo multiplier.
+const BOSS_BONUS: i32 = 100;
+
+/// Horizontal distance of a splitter's fragments from where it broke.
+const SPLIT_OFFSET: f32 = 40.0;
+
+/// Seconds a slow-time power-up lasts.
+pub const SLOW_TIME_DURATION: f32 = 5.0;
+
+/// Speed of falling entities while slow time is active.
+const SLOW_TIME_FACTOR: f32 = 0.5;
+
+/// Points per target destroyed by a bomb.
+const BOMB_POINTS: i32 = 10;
+
+/// Length of one fixed simulation tick in seconds. Stepping in fixed ticks
+/// keeps runs identical regardless of the front end's frame rate.
+pub const TICK: f32 = 1.0 / 120.0;
+
+/// Input fed into the simulation for a single step.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum InputEvent {
+    Char(char),
+}
+
+/// Something that happened during a simulation step. Front ends react to
+/// these with particles, flashes and sounds.
+#[derive(Clone, Debug, PartialEq)]
+pub enum GameEvent {
+    LetterSpawned { char: char, x: f32 },
+    LetterDestroyed { char: char, x: f32, y: f32, points: i32 },
+    /// The first press on an armored letter broke its armor.
+    ArmorCracked { x: f32, y: f32 },
+    /// A splitter broke into two new letters.
+    LetterSplit { x: f32, y: f32 },
+    PowerUpCollected { power_up: PowerUp, x: f32, y: f32 },
+    /// The shield took a target's ground hit instead of the player.
+    ShieldAbsorbed,
+    /// A bomb destroyed every target on screen, at these positions.
+    BombDetonated { targets: Vec<(f32, f32)> },
+    WordSpawned { text: String, x: f32 },
+    WordProgress { x: f32, y: f32 },
+    WordDestroyed { text: String, x: f32, y: f32, points: i32 },
+    WrongKey { char: char },
+    LetterMissed { char: char, x: f32 },
+    WordMissed { text: String, x: f32 },
+    /// A target reached the ground and cost a life; `lives` are left.
+    LifeLost { lives: i32 },
+    /// The combo reached another multiple of [`COMBO_MILESTONE`].
+    ComboMilestone { combo: i32 },
+    /// The level's clock ran out and its boss is on the way.
+    BossAppeared { name: String },
+    BossHit { x: f32, y: f32, points: i32 },
+    /// The boss moved on to `phase`, counted from 0.
+    BossPhaseChanged { phase: usize },
+    /// A wrong key made the boss fire at the player.
+    BossFired { x: f32, y: f32 },
+    /// A boss shot reached the player.
+    BossShotHit { x: f32 },
+    BossDefeated { x: f32, y: f32, points: i32 },
+    LevelComplete,
+    GameOver,
+}
+
+/// Rules a run is played under; recorded in replays.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub enum RunMode {
+    #[default]
+    Normal,
+    /// Targets reaching the ground only count as misses; there is no game
+    /// over and no time limit, so the run lasts until the player quits.
+    Practice,
+    /// The first wrong key ends the run, as does a target reaching the
+    /// ground unless `ground_miss` allows one.
+    SuddenDeath { ground_miss: bool },
+    /// Exactly `targets` targets, kept coming up to [`TIME_ATTACK_CAP`] on
+    /// screen at once; the result is the time to clear them all, plus
+    /// penalties for wrong keys and targets reaching the ground.
+    TimeAttack { targets: u32 },
+}
+
+impl RunMode {
+    pub const ALL: [Self; 5] = [
+        Self::Normal,
+        Self::Practice,
+        Self::SuddenDeath { ground_miss: false },
+        Self::SuddenDeath { ground_miss: true },
+        Self::TimeAttack { targets: 100 },
+    ];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::Normal => "Normal",
+            Self::Practice => "Practice",
+            Self::SuddenDeath { ground_miss: false } => "Sudden death",
+            Self::SuddenDeath { ground_miss: true } => "Sudden death (1 miss)",
+            Self::TimeAttack { .. } => "Time attack",
+        }
+    }
+
+    /// The mode after this one, for cycling through a setting.
+    pub fn next(self) -> Self {
+        let index = Self::ALL.iter().position(|&mode| mode == self).unwrap_or(0);
+        Self::ALL[(index + 1) % Self::ALL.len()]
+    }
+}
+
+#[derive(PartialEq, Clone, Copy, Debug)]
+pub enum RunState {
+    Playing,
+    LevelComplete,
+    GameOver,
+}
+
+/// Headline results of a finished (or abandoned) run.
+#[derive(Clone, Debug, PartialEq)]
+pub struct RunSummary {
+    pub level_id: String,
+    pub seed: u64,
+    pub score: i32,
+    pub accuracy: i32,
+    pub net_wpm: f32,
+    pub completed: bool,
+    /// Seconds of play.
+    pub duration: f32,
+    /// Seconds added on top of `duration` by time-attack penalties.
+    pub penalty: f32,
+}
+
+/// A single level run, free of any window, input or rendering calls.
+pub struct Simulation {
+    state: RunState,
+    seed: u64,
+    /// ChaCha8 rather than `StdRng`, whose stream may change between `rand`
+    /// releases; replays and shared seeds depend on it staying fixed.
+    rng: ChaCha8Rng,
+    player: Player,
+    letters: Vec<Letter>,
+    words: Vec<Word>,
+    boss: Option<Boss>,
+    boss_shots: Vec<BossShot>,
+    boss_defeated: bool,
+    /// A shield power-up is waiting to absorb a ground hit.
+    shield: bool,
+    /// Seconds of slow time left.
+    slow_time: f32,
+    level: Level,
+    /// Layout `level` was remapped to, recorded in replays.
+    layout: KeyboardLayout,
+    mode: RunMode,
+    spawner_config: SpawnerConfig,
+    spawner: Box<dyn SpawnStrategy>,
+    pace: Pace,
+    field_width: f32,
+    field_height: f32,
+    score: i32,
+    combo: i32,
+    /// Targets that reached the ground.
+    misses: i32,
+    /// Targets spawned and destroyed or landed, for time attack.
+    spawned: u32,
+    cleared: u32,
+    penalty: f32,
+    /// Time with penalties at every [`SPLIT_SIZE`] cleared targets.
+    splits: Vec<f32>,
+    correct_count: i32,
+    total_count: i32,
+    spawn_timer: f32,
+    level_timer: f32,
+    last_key_time: f32,
+    stats: TypingStats,
+    key_analytics: KeyAnalytics,
+    tick: u64,
+    accumulator: f32,
+    pending: Vec<InputEvent>,
+    recorded: Vec<ReplayKey>,
+    /// Keys being played back, sorted by tick.
+    playback: Option<Vec<ReplayKey>>,
+    /// Index of the next key in `playback` to apply.
+    playback_cursor: usize,
+    /// Tick the recording stopped on, if it did.
+    playback_end: Option<u64>,
+}
+
+impl Simulation {
+    /// Creates a run whose spawns are fully determined by `seed`.
+    pub fn new(level: Level, field_width: f32, field_height: f32, seed: u64) -> Self {
+        Self {
+            state: RunState::Playing,
+            seed,
+            rng: ChaCha8Rng::seed_from_u64(seed),
+            player: Player::new(field_width / 2.0, field_height - GROUND_MARGIN - 30.0),
+            letters: Vec::new(),
+            words: Vec::new(),
+            boss: None,
+            boss_shots: Vec::new(),
+            boss_defeated: false,
+            shield: false,
+            slow_time: 0.0,
+            pace: Pace::of(&level),
+            level,
+            layout: KeyboardLayout::default(),
+            mode: RunMode::Normal,
+            spawner_config: SpawnerConfig::Uniform,
+            spawner: SpawnerConfig::Uniform.build(),
+            field_width,
+            field_height,
+            score: 0,
+            combo: 0,
+            misses: 0,
+            spawned: 0,
+            cleared: 0,
+            penalty: 0.0,
+            splits: Vec::new(),
+            correct_count: 0,
+            total_count: 0,
+            spawn_timer: 0.0,
+            level_timer: 0.0,
+            last_key_time: 0.0,
+            stats: TypingStats::new(),
+            key_analytics: KeyAnalytics::new(),
+            tick: 0,
+            accumulator: 0.0,
+            pending: Vec::new(),
+            recorded: Vec::new(),
+            playback: None,
+            playback_cursor: 0,
+            playback_end: None,
+        }
+    }
+
+    /// Creates a run that replays the recorded key presses of `replay`,
+    /// ignoring any live input passed to [`Simulation::step`].
+    pub fn from_replay(replay: &Replay, level: Level) -> Self {
+        let mut sim = Self::new(level, replay.field_width, replay.field_height, replay.seed);
+        sim.set_spawner(replay.spawner);
+        sim.set_mode(replay.mode);
+        sim.set_layout(replay.layout);
+        let mut keys = replay.keys.clone();
+        keys.sort_by_key(|key| key.tick);
+        sim.playback = Some(keys);
+        sim.playback_end = replay.end_tick;
+        sim
+    }
+
+    /// Switches how targets are chosen; call before the first step.
+    pub fn set_spawner(&mut self, config: SpawnerConfig) {
+        self.spawner_config = config;
+        self.spawner = config.build();
+    }
+
+    /// Switches the rules of the run; call before the first step.
+    pub fn set_mode(&mut self, mode: RunMode) {
+        self.mode = mode;
+    }
+
+    /// Records the layout the level was remapped to, so replays of this
+    /// run are remapped the same way.
+    pub fn set_layout(&mut self, layout: KeyboardLayout) {
+        self.layout = layout;
+    }
+
+    /// Advances the run by `delta` seconds of wall time, applying `inputs`
+    /// at the start of the next fixed tick.
+    pub fn step(&mut self, delta: f32, inputs: &[InputEvent]) -> Vec<GameEvent> {
+        let mut events = Vec::new();
+        self.pending.extend_from_slice(inputs);
+        self.accumulator += delta;
+
+        while self.accumulator >= TICK && self.state == RunState::Playing && !self.playback_ended() {
+            self.accumulator -= TICK;
+            let inputs = std::mem::take(&mut self.pending);
+            events.extend(self.tick(&inputs));
+        }
+        events
+    }
+
+    /// Advances the run by exactly one [`TICK`].
+    pub fn tick(&mut self, inputs: &[InputEvent]) -> Vec<GameEvent> {
+        let mut events = Vec::new();
+        if self.state != RunState::Playing || self.playback_ended() {
+            return events;
+        }
+
+        let inputs = match &self.playback {
+            Some(keys) => {
+                let start = self.playback_cursor;
+                while keys.get(self.playback_cursor).is_some_and(|key| key.tick <= self.tick) {
+                    self.playback_cursor += 1;
+                }
+                keys[start..self.playback_cursor].iter().map(|key| InputEvent::Char(key.char)).collect()
+            }
+            None => inputs.to_vec(),
+        };
+        for input in &inputs {
+            let InputEvent::Char(c) = *input;
+            self.recorded.push(ReplayKey { tick: self.tick, char: c });
+        }
+
+        self.update_playing(TICK, &inputs, &mut events);
+        self.tick += 1;
+        events
+    }
+
+    /// Snapshot of everything needed to reproduce this run so far.
+    pub fn replay(&self) -> Replay {
+        Replay {
+            version: Replay::VERSION,
+            seed: self.seed,
+            level_id: self.level.id.clone(),
+            field_width: self.field_width,
+            field_height: self.field_height,
+            spawner: self.spawner_config,
+            mode: self.mode,
+            end_tick: Some(self.tick),
+            layout: self.layout,
+            keys: self.recorded.clone(),
+        }
+    }
+
+    pub fn is_playback(&self) -> bool {
+        self.playback.is_some()
+    }
+
+    /// Whether playback reached the tick its recording stopped on, e.g.
+    /// a practice run quit from the pause menu.
+    pub fn playback_ended(&self) -> bool {
+        self.playback_end.is_some_and(|end| self.tick >= end)
+    }
+
+    fn update_playing(&mut self, delta: f32, inputs: &[InputEvent], events: &mut Vec<GameEvent>) {
+        self.level_timer += delta;
+        self.spawn_timer += delta;
+
+        // A level with a boss ends with it once the clock runs out
+        let boss_due = matches!(self.mode, RunMode::Normal | RunMode::SuddenDeath { .. })
+            && self.level_timer >= self.level.duration
+            && self.boss.is_none()
+            && !self.boss_defeated;
+        if boss_due {
+            if let Some(config) = &self.level.boss {
+                let mut boss = Boss::new(&config.name, &config.phases, self.field_width / 2.0);
+                boss.last_hit_at = self.level_timer;
+                events.push(GameEvent::BossAppeared { name: boss.name.clone() });
+                self.boss = Some(boss);
+            }
+        }
+
+        // Spawn new letters; time attack keeps the screen filled instead,
+        // and nothing new falls while a boss is out
+        let due = self.boss.is_none() && match self.mode {
+            RunMode::TimeAttack { targets } => {
+                self.spawned < targets
+                    && self.letters.len() + self.words.len() < TIME_ATTACK_CAP
+                    && self.spawn_timer >= TIME_ATTACK_GAP
+            }
+            _ => self.spawn_timer >= self.pace.spawn_rate,
+        };
+        if due {
+            self.spawn_target(events);
+            self.spawn_timer = 0.0;
+        }
+
+        // Update letters, slowed down while slow time lasts
+        let fall_delta = if self.slow_time > 0.0 { delta * SLOW_TIME_FACTOR } else { delta };
+        self.slow_time = (self.slow_time - delta).max(0.0);
+        for letter in &mut self.letters {
+            letter.update(fall_delta);
+        }
+        for word in &mut self.words {
+            word.update(fall_delta);
+        }
+        if let Some(boss) = &mut self.boss {
+            boss.update(delta);
+        }
+        for shot in &mut self.boss_shots {
+            shot.update(fall_delta);
+        }
+
+        // Check for letters that hit the ground
+        let ground_y = self.ground_y();
+        let mut i = 0;
+        while i < self.letters.len() {
+            if self.letters[i].has_landed(ground_y) {
+                let letter = self.letters.remove(i);
+                self.key_analytics.record_miss(letter.char);
+                events.push(GameEvent::LetterMissed { char: letter.char, x: letter.x });
+                if !letter.fragment {
+                    self.target_cleared();
+                }
+                self.target_landed(events);
+            } else {
+                i += 1;
+            }
+        }
+        let mut i = 0;
+        while i < self.words.len() {
+            if self.words[i].has_landed(ground_y) {
+                let word = self.words.remove(i);
+                if let Some(next) = word.next_char() {
+                    self.key_analytics.record_miss(next);
+                }
+                events.push(GameEvent::WordMissed { text: word.text.iter().collect(), x: word.x });
+                self.target_cleared();
+                self.target_landed(events);
+            } else {
+                i += 1;
+            }
+        }
+
+        let player_y = self.player.y;
+        let mut i = 0;
+        while i < self.boss_shots.len() {
+            if self.boss_shots[i].has_hit(player_y) {
+                let shot = self.boss_shots.remove(i);
+                events.push(GameEvent::BossShotHit { x: shot.x });
+                if self.mode == RunMode::Normal {
+                    self.lose_life(events);
+                }
+            } else {
+                i += 1;
+            }
+        }
+
+        // Handle keyboard input
+        for input in inputs {
+            if self.state != RunState::Playing {
+                break;
+            }
+            match *input {
+                InputEvent::Char(c) => self.handle_letter_typed(c, events),
+            }
+        }
+
+        // Check level completion
+        let finished = match self.mode {
+            RunMode::Practice => false,
+            RunMode::TimeAttack { targets } => {
+                self.spawned >= targets && self.letters.is_empty() && self.words.is_empty()
+            }
+            _ => {
+                self.level_timer >= self.level.duration
+                    && (self.level.boss.is_none() || self.boss_defeated)
+            }
+        };
+        if self.state == RunState::Playing && finished && self.player.lives > 0 {
+            self.state = RunState::LevelComplete;
+            events.push(GameEvent::LevelComplete);
+        }
+    }
+
+    /// A target reached the ground: a miss and, depending on the mode, a
+    /// life or the whole run, unless a shield takes the hit.
+    fn target_landed(&mut self, events: &mut Vec<GameEvent>) {
+        if self.shield {
+            self.shield = false;
+            events.push(GameEvent::ShieldAbsorbed);
+            return;
+        }
+        self.misses += 1;
+        self.combo = 0;
+        match self.mode {
+            RunMode::Normal => self.lose_life(events),
+            RunMode::Practice => {}
+            RunMode::TimeAttack { .. } => self.penalty += GROUND_PENALTY,
+            RunMode::SuddenDeath { ground_miss } => {
+                if !(ground_miss && self.misses == 1) {
+                    self.end_run(events);
+                }
+            }
+        }
+    }
+
+    /// Counts a spawned target as destroyed or landed, taking a time-attack
+    /// split every [`SPLIT_SIZE`] targets.
+    fn target_cleared(&mut self) {
+        self.cleared += 1;
+        if self.cleared.is_multiple_of(SPLIT_SIZE) {
+            self.splits.push(self.attack_time());
+        }
+    }
+
+    fn lose_life(&mut self, events: &mut Vec<GameEvent>) {
+        self.player.lives -= 1;
+        self.combo = 0;
+        events.push(GameEvent::LifeLost { lives: self.player.lives });
+
+        if self.player.lives <= 0 {
+            self.end_run(events);
+        }
+    }
+
+    fn end_run(&mut self, events: &mut Vec<GameEvent>) {
+        if self.state == RunState::Playing {
+            self.state = RunState::GameOver;
+            events.push(GameEvent::GameOver);
+        }
+    }
+
+    fn handle_letter_typed(&mut self, typed_char: char, events: &mut Vec<GameEvent>) {
+        // Keystrokes go to the locked word until it is finished
+        if let Some(index) = self.words.iter().position(|word| word.is_locked) {
+            let expected = self.words[index].next_char();
+            if expected.is_some_and(|c| self.level.matches(c, typed_char)) {
+                self.advance_word(index, events);
+            } else {
+                self.handle_wrong_key(typed_char, events);
+            }
+            return;
+        }
+
+        // Find matching letter or word start (closest to ground)
+        let mut found_letter = None;
+        let mut found_word = None;
+        // Anything already showing at the top edge counts
+        let mut max_y = f32::NEG_INFINITY;
+
+        for (i, letter) in self.letters.iter().enumerate() {
+            if self.level.matches(letter.char, typed_char) && letter.is_in_view() && letter.y > max_y {
+                found_letter = Some(i);
+                max_y = letter.y;
+            }
+        }
+        for (i, word) in self.words.iter().enumerate() {
+            let starts = word.next_char().is_some_and(|c| self.level.matches(c, typed_char));
+            if starts && word.is_in_view() && word.y > max_y {
+                found_word = Some(i);
+                found_letter = None;
+                max_y = word.y;
+            }
+        }
+
+        if let Some(index) = found_word {
+            self.words[index].is_locked = true;
+            self.advance_word(index, events);
+            return;
+        }
+
+        // The boss only takes keys no falling target wants
+        let boss_expects = self.boss.as_ref().and_then(Boss::next_char);
+        if found_letter.is_none() && boss_expects.is_some_and(|c| self.level.matches(c, typed_char)) {
+            self.hit_boss(events);
+            return;
+        }
+
+        // A stray space between targets is not a mistake
+        if found_letter.is_none() && typed_char.is_whitespace() {
+            return;
+        }
+
+        // Armor takes the first press
+        if let Some(letter) = found_letter.map(|index| &mut self.letters[index]) {
+            if letter.armor > 0 {
+                letter.armor -= 1;
+                let (c, x, y) = (letter.char, letter.x, letter.y);
+                let reaction = self.level_timer - letter.spawned_at;
+                letter.spawned_at = self.level_timer;
+                self.count_keystroke(true);
+                self.bump_combo(events);
+                self.key_analytics.record_hit(c, reaction);
+                events.push(GameEvent::ArmorCracked { x, y });
+                return;
+            }
+        }
+
+        if let Some(index) = found_letter {
+            // Correct letter typed!
+            let letter = self.letters.remove(index);
+            self.count_keystroke(true);
+            self.bump_combo(events);
+
+            // Calculate points
+            let base_points = 10;
+            let combo_multiplier = 1 + (self.combo / COMBO_MILESTONE);
+            let points = base_points * combo_multiplier;
+            self.score += points;
+
+            // Track stats
+            self.key_analytics.record_hit(letter.char, self.level_timer - letter.spawned_at);
+
+            if !letter.fragment {
+                self.target_cleared();
+            }
+            events.push(GameEvent::LetterDestroyed {
+                char: letter.char,
+                x: letter.x,
+                y: letter.y,
+                points,
+            });
+            if letter.behavior == Behavior::Splitter {
+                self.split(&letter, events);
+            }
+            if let Some(power_up) = letter.power_up {
+                self.apply_power_up(power_up, letter.x, letter.y, events);
+            }
+        } else {
+            self.handle_wrong_key(typed_char, events);
+        }
+    }
+
+    fn apply_power_up(&mut self, power_up: PowerUp, x: f32, y: f32, events: &mut Vec<GameEvent>) {
+        events.push(GameEvent::PowerUpCollected { power_up, x, y });
+        match power_up {
+            PowerUp::Shield => self.shield = true,
+            PowerUp::SlowTime => self.slow_time = SLOW_TIME_DURATION,
+            PowerUp::Bomb => self.detonate(events),
+            PowerUp::ExtraLife => {
+                self.player.lives = (self.player.lives + 1).min(self.player.max_lives);
+            }
+        }
+    }
+
+    /// Destroys every falling target and boss shot on screen.
+    fn detonate(&mut self, events: &mut Vec<GameEvent>) {
+        let mut targets = Vec::new();
+        for letter in std::mem::take(&mut self.letters) {
+            if !letter.fragment {
+                self.target_cleared();
+            }
+            targets.push((letter.x, letter.y));
+        }
+        for word in std::mem::take(&mut self.words) {
+            self.target_cleared();
+            targets.push((word.x, word.y));
+        }
+        self.boss_shots.clear();
+        self.score += BOMB_POINTS * targets.len() as i32;
+        events.push(GameEvent::BombDetonated { targets });
+    }
+
+    /// Drops two fragment letters where a splitter was destroyed, drawn from
+    /// the level's letters.
+    fn split(&mut self, splitter: &Letter, events: &mut Vec<GameEvent>) {
+        if self.level.letters.is_empty() {
+            return;
+        }
+        let margin = self.field_width * SPAWN_MARGIN;
+        for offset in [-SPLIT_OFFSET, SPLIT_OFFSET] {
+            let index = self.rng.gen_range(0..self.level.letters.len() as u32) as usize;
+            let x = (splitter.x + offset).clamp(margin, (self.field_width - margin).max(margin));
+            let mut letter = Letter::new(self.level.letters[index], x, splitter.speed);
+            letter.y = splitter.y;
+            letter.size *= 0.75;
+            letter.fragment = true;
+            letter.spawned_at = self.level_timer;
+            self.letters.push(letter);
+        }
+        events.push(GameEvent::LetterSplit { x: splitter.x, y: splitter.y });
+    }
+
+    /// Accepts the next character of the word at `index`, destroying the
+    /// word once it is fully typed.
+    fn advance_word(&mut self, index: usize, events: &mut Vec<GameEvent>) {
+        let word = &mut self.words[index];
+        let typed = word.next_char().expect("locked word has characters left");
+        word.typed += 1;
+        let reaction = self.level_timer - word.last_hit_at;
+        word.last_hit_at = self.level_timer;
+        self.count_keystroke(true);
+        self.bump_combo(events);
+        self.key_analytics.record_hit(typed, reaction);
+
+        if !self.words[index].is_complete() {
+            let word = &self.words[index];
+            events.push(GameEvent::WordProgress { x: word.x, y: word.y });
+            return;
+        }
+
+        let word = self.words.remove(index);
+
+        // Longer words earn a bonus on top of 10 points per character
+        let length = word.text.len() as i32;
+        let base_points = 10 * length + 5 * (length - 1);
+        let combo_multiplier = 1 + (self.combo / COMBO_MILESTONE);
+        let points = base_points * combo_multiplier;
+        self.score += points;
+
+        self.target_cleared();
+        events.push(GameEvent::WordDestroyed {
+            text: word.text.iter().collect(),
+            x: word.x,
+            y: word.y,
+            points,
+        });
+    }
+
+    /// Chips the boss with a correct key, destroying it after its last
+    /// phase.
+    fn hit_boss(&mut self, events: &mut Vec<GameEvent>) {
+        let Some(boss) = &mut self.boss else {
+            return;
+        };
+        let typed = boss.next_char().expect("boss has characters left");
+        let reaction = self.level_timer - boss.last_hit_at;
+        boss.last_hit_at = self.level_timer;
+        let progress = boss.advance();
+        let (x, y, phase, phases) = (boss.x, boss.y, boss.phase, boss.phases.len() as i32);
+
+        self.count_keystroke(true);
+        self.bump_combo(events);
+        self.key_analytics.record_hit(typed, reaction);
+
+        let combo_multiplier = 1 + (self.combo / COMBO_MILESTONE);
+        let points = 10 * combo_multiplier;
+        self.score += points;
+        events.push(GameEvent::BossHit { x, y, points });
+
+        match progress {
+            BossProgress::Chipped => {}
+            BossProgress::PhaseCleared => events.push(GameEvent::BossPhaseChanged { phase }),
+            BossProgress::Defeated => {
+                let points = BOSS_BONUS * phases * combo_multiplier;
+                self.score += points;
+                self.boss = None;
+                self.boss_shots.clear();
+                self.boss_defeated = true;
+                events.push(GameEvent::BossDefeated { x, y, points });
+            }
+        }
+    }
+
+    fn bump_combo(&mut self, events: &mut Vec<GameEvent>) {
+        self.combo += 1;
+        if self.combo % COMBO_MILESTONE == 0 {
+            events.push(GameEvent::ComboMilestone { combo: self.combo });
+        }
+    }
+
+    fn handle_wrong_key(&mut self, typed_char: char, events: &mut Vec<GameEvent>) {
+        // Wrong letter!
+        self.count_keystroke(false);
+        self.combo = 0;
+        self.score = (self.score - 2).max(0);
+        if let Some(expected) = self.expected_char() {
+            self.key_analytics.record_mistype(expected, typed_char);
+        }
+
+        events.push(GameEvent::WrongKey { char: typed_char });
+        if let Some(boss) = &self.boss {
+            let shot = BossShot::new(boss.x, boss.y, self.player.x, self.player.y, BOSS_SHOT_SPEED);
+            self.boss_shots.push(shot);
+            events.push(GameEvent::BossFired { x: boss.x, y: boss.y });
+        }
+        match self.mode {
+            RunMode::SuddenDeath { .. } => self.end_run(events),
+            RunMode::TimeAttack { .. } => self.penalty += WRONG_KEY_PENALTY,
+            _ => {}
+        }
+    }
+
+    /// The character the player should be typing: the locked word's next
+    /// character, otherwise the target closest to the ground.
+    fn expected_char(&self) -> Option<char> {
+        if let Some(word) = self.words.iter().find(|word| word.is_locked) {
+            return word.next_char();
+        }
+
+        let lowest = |a: (f32, char), b: (f32, char)| if b.0 > a.0 { b } else { a };
+        let lowest_letter = self.letters.iter().map(|l| (l.y, l.char)).reduce(lowest);
+        let lowest_word = self
+            .words
+            .iter()
+            .filter_map(|w| w.next_char().map(|c| (w.y, c)))
+            .reduce(lowest);
+        match (lowest_letter, lowest_word) {
+            (Some(letter), Some(word)) => Some(if word.0 > letter.0 { word.1 } else { letter.1 }),
+            (letter, word) => letter.or(word).map(|(_, c)| c),
+        }
+        .or_else(|| self.boss.as_ref().and_then(Boss::next_char))
+    }
+
+    fn count_keystroke(&mut self, correct: bool) {
+        self.total_count += 1;
+        if correct {
+            self.correct_count += 1;
+        }
+        self.last_key_time = self.level_timer;
+        self.stats.record(self.level_timer, correct);
+    }
+
+    /// Spawns whatever the spawn strategy picks at its current pace.
+    fn spawn_target(&mut self, events: &mut Vec<GameEvent>) {
+        let ctx = SpawnContext {
+            level: &self.level,
+            analytics: &self.key_analytics,
+            stats: &self.stats,
+        };
+        self.pace = self.spawner.pace(&ctx);
+        let Some(target) = self.spawner.pick(&ctx, &mut self.rng) else {
+            return;
+        };
+
+        match target {
+            Target::Letter(character) => {
+                let behavior = self.pick_behavior();
+                let mut margin = self.field_width * SPAWN_MARGIN;
+                if behavior == Behavior::Zigzag {
+                    margin += Letter::ZIGZAG_AMPLITUDE;
+                }
+                let x = self.spawn_x(margin);
+                let mut letter = Letter::new(character, x, self.pace.fall_speed);
+                letter.set_behavior(behavior);
+                letter.power_up = self.pick_power_up();
+                letter.spawned_at = self.level_timer;
+                self.letters.push(letter);
+                self.spawned += 1;
+                events.push(GameEvent::LetterSpawned { char: character, x });
+            }
+            Target::Word(text) => {
+                let mut word = Word::new(&text, 0.0, self.pace.fall_speed);
+                word.x = self.spawn_x((word.width() / 2.0).max(self.field_width * SPAWN_MARGIN));
+                word.last_hit_at = self.level_timer;
+                events.push(GameEvent::WordSpawned { text, x: word.x });
+                self.words.push(word);
+                self.spawned += 1;
+            }
+        }
+    }
+
+    /// Rolls a behavior from the level's mix. Levels without one draw
+    /// nothing, so their seeds keep producing the same runs.
+    fn pick_behavior(&mut self) -> Behavior {
+        if self.level.behaviors.is_empty() {
+            return Behavior::Normal;
+        }
+        let mut roll = self.rng.gen_range(0.0..1.0);
+        for (&behavior, &share) in &self.level.behaviors {
+            if roll < share {
+                return behavior;
+            }
+            roll -= share;
+        }
+        Behavior::Normal
+    }
+
+    /// Rolls whether a new letter carries a power-up at the level's drop
+    /// rate; like [`Simulation::pick_behavior`], draws nothing at rate 0.
+    fn pick_power_up(&mut self) -> Option<PowerUp> {
+        if self.level.power_up_rate <= 0.0 || self.rng.gen_range(0.0..1.0) >= self.level.power_up_rate {
+            return None;
+        }
+        let index = self.rng.gen_range(0..PowerUp::ALL.len() as u32) as usize;
+        Some(PowerUp::ALL[index])
+    }
+
+    fn spawn_x(&mut self, margin: f32) -> f32 {
+        if self.field_width > margin * 2.0 {
+            self.rng.gen_range(margin..self.field_width - margin)
+        } else {
+            self.field_width / 2.0
+        }
+    }
+
+    /// Height of the ground line targets must not reach.
+    pub fn ground_y(&self) -> f32 {
+        self.field_height - GROUND_MARGIN
+    }
+
+    pub fn field_width(&self) -> f32 {
+        self.field_width
+    }
+
+    pub fn seed(&self) -> u64 {
+        self.seed
+    }
+
+    pub fn state(&self) -> RunState {
+        self.state
+    }
+
+    pub fn player(&self) -> &Player {
+        &self.player
+    }
+
+    pub fn letters(&self) -> &[Letter] {
+        &self.letters
+    }
+
+    pub fn words(&self) -> &[Word] {
+        &self.words
+    }
+
+    pub fn boss(&self) -> Option<&Boss> {
+        self.boss.as_ref()
+    }
+
+    pub fn boss_shots(&self) -> &[BossShot] {
+        &self.boss_shots
+    }
+
+    pub fn has_shield(&self) -> bool {
+        self.shield
+    }
+
+    /// Seconds of slow time left; 0 when inactive.
+    pub fn slow_time(&self) -> f32 {
+        self.slow_time
+    }
+
+    pub fn spawner_config(&self) -> SpawnerConfig {
+        self.spawner_config
+    }
+
+    /// Pace the spawner chose for the most recent spawn.
+    pub fn pace(&self) -> Pace {
+        self.pace
+    }
+
+    pub fn level(&self) -> &Level {
+        &self.level
+    }
+
+    pub fn score(&self) -> i32 {
+        self.score
+    }
+
+    /// Fixed ticks simulated so far.
+    pub fn ticks(&self) -> u64 {
+        self.tick
+    }
+
+    pub fn mode(&self) -> RunMode {
+        self.mode
+    }
+
+    pub fn misses(&self) -> i32 {
+        self.misses
+    }
+
+    /// Targets destroyed or landed so far.
+    pub fn cleared(&self) -> u32 {
+        self.cleared
+    }
+
+    /// Elapsed time plus penalties: the time-attack result once complete.
+    pub fn attack_time(&self) -> f32 {
+        self.level_timer + self.penalty
+    }
+
+    pub fn splits(&self) -> &[f32] {
+        &self.splits
+    }
+
+    pub fn combo(&self) -> i32 {
+        self.combo
+    }
+
+    pub fn correct_count(&self) -> i32 {
+        self.correct_count
+    }
+
+    pub fn total_count(&self) -> i32 {
+        self.total_count
+    }
+
+    pub fn level_timer(&self) -> f32 {
+        self.level_timer
+    }
+
+    pub fn last_key_time(&self) -> f32 {
+        self.last_key_time
+    }
+
+    pub fn stats(&self) -> &TypingStats {
+        &self.stats
+    }
+
+    pub fn key_analytics(&self) -> &KeyAnalytics {
+        &self.key_analytics
+    }
+
+    /// Accuracy as a whole percentage; 100 before any key is pressed.
+    pub fn accuracy(&self) -> i32 {
+        if self.total_count > 0 {
+            (self.correct_count as f32 / self.total_count as f32 * 100.0) as i32
+        } else {
+            100
+        }
+    }
+
+    pub fn summary(&self) -> RunSummary {
+        RunSummary {
+            level_id: self.level.id.clone(),
+            seed: self.seed,
+            score: self.score,
+            accuracy: self.accuracy(),
+            net_wpm: self.stats.net_wpm(self.level_timer),
+            completed: self.state == RunState::LevelComplete,
+            duration: self.level_timer,
+            penalty: self.penalty,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn level(duration: f32) -> Level {
+        Level {
+            id: "test".to_string(),
+            name: "Test".to_string(),
+            letters: vec!['f'],
+            words: Vec::new(),
+            fall_speed: 300.0,
+            spawn_rate: 0.5,
+            duration,
+            description: String::new(),
+            hint: String::new(),
+            case_sensitive: false,
+            behaviors: Default::default(),
+            power_up_rate: 0.0,
+            boss: None,
+        }
+    }
+
+    /// Ticks without input until an event matching `until` is emitted.
+    fn run_until(sim: &mut Simulation, until: impl Fn(&GameEvent) -> bool) -> Vec<GameEvent> {
+        for _ in 0..120 * 60 {
+            let events = sim.tick(&[]);
+            if events.iter().any(&until) {
+                return events;
+            }
+        }
+        panic!("event never happened");
+    }
+
+    #[test]
+    fn typing_a_letter_destroys_it() {
+        let mut sim = Simulation::new(level(30.0), 800.0, 600.0, 1);
+        run_until(&mut sim, |e| matches!(e, GameEvent::LetterSpawned { .. }));
+        // Letters spawn above the field and only take keys once in view
+        while !sim.letters()[0].is_in_view() {
+            sim.tick(&[]);
+        }
+
+        let events = sim.tick(&[InputEvent::Char('f')]);
+        assert!(events.iter().any(|e| matches!(e, GameEvent::LetterDestroyed { char: 'f', .. })));
+        assert!(sim.letters().is_empty());
+        assert!(sim.score() > 0);
+        assert_eq!(sim.accuracy(), 100);
+    }
+
+    #[test]
+    fn sudden_death_takes_a_letter_still_entering_the_screen() {
+        let mut sim = Simulation::new(level(30.0), 800.0, 600.0, 1);
+        sim.set_mode(RunMode::SuddenDeath { ground_miss: false });
+        run_until(&mut sim, |e| matches!(e, GameEvent::LetterSpawned { .. }));
+        while !sim.letters()[0].is_in_view() {
+            sim.tick(&[]);
+        }
+        assert!(sim.letters()[0].y < 0.0);
+
+        let events = sim.tick(&[InputEvent::Char('f')]);
+        assert!(events.iter().any(|e| matches!(e, GameEvent::LetterDestroyed { char: 'f', .. })));
+        assert_eq!(sim.state(), RunState::Playing);
+    }
+
+    #[test]
+    fn a_letter_reaching_the_ground_costs_a_life() {
+        let mut sim = Simulation::new(level(30.0), 800.0, 600.0, 1);
+        let lives = sim.player().lives;
+
+        let events = run_until(&mut sim, |e| matches!(e, GameEvent::LetterMissed { .. }));
+        assert!(events.contains(&GameEvent::LifeLost { lives: lives - 1 }));
+        assert_eq!(sim.player().lives, lives - 1);
+        assert_eq!(sim.misses(), 1);
+    }
+
+    #[test]
+    fn losing_every_life_ends_the_run() {
+        let mut sim = Simulation::new(level(600.0), 800.0, 600.0, 1);
+        run_until(&mut sim, |e| *e == GameEvent::GameOver);
+
+        assert_eq!(sim.state(), RunState::GameOver);
+        assert_eq!(sim.player().lives, 0);
+        assert!(!sim.summary().completed);
+    }
+
+    #[test]
+    fn surviving_the_clock_completes_the_level() {
+        let mut sim = Simulation::new(level(2.0), 800.0, 600.0, 1);
+        for _ in 0..120 * 5 {
+            let in_view = sim.letters().iter().filter(|letter| letter.is_in_view()).count();
+            let inputs = vec![InputEvent::Char('f'); in_view];
+            sim.tick(&inputs);
+            if sim.state() != RunState::Playing {
+                break;
+            }
+        }
+
+        assert_eq!(sim.state(), RunState::LevelComplete);
+        assert_eq!(sim.misses(), 0);
+        assert_eq!(sim.accuracy(), 100);
+        assert!(sim.summary().completed);
+    }
+}
@@ -0,0 +1,205 @@
+use ::rand::{Rng, RngCore};
+use serde::{Deserialize, Serialize};
+use crate::levels::Level;
+use crate::stats::{KeyAnalytics, TypingStats};
+
+/// What a strategy asks the simulation to spawn next.
+#[derive(Clone, Debug, PartialEq)]
+pub enum Target {
+    Letter(char),
+    Word(String),
+}
+
+/// Fall speed and spawn interval applied to new spawns.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Pace {
+    pub fall_speed: f32,
+    pub spawn_rate: f32,
+}
+
+impl Pace {
+    pub fn of(level: &Level) -> Self {
+        Self {
+            fall_speed: level.fall_speed,
+            spawn_rate: level.spawn_rate,
+        }
+    }
+}
+
+/// Read-only view of the run handed to a strategy on every spawn.
+pub struct SpawnContext<'a> {
+    pub level: &'a Level,
+    pub analytics: &'a KeyAnalytics,
+    pub stats: &'a TypingStats,
+}
+
+/// Decides what falls next and how fast. Strategies must draw randomness
+/// only from `rng` so runs stay reproducible from their seed.
+pub trait SpawnStrategy {
+    fn pick(&mut self, ctx: &SpawnContext, rng: &mut dyn RngCore) -> Option<Target>;
+
+    fn pace(&mut self, ctx: &SpawnContext) -> Pace {
+        Pace::of(ctx.level)
+    }
+}
+
+/// Serializable choice of strategy, recorded in replays.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub enum SpawnerConfig {
+    #[default]
+    Uniform,
+    Adaptive { min_accuracy: f32, max_accuracy: f32 },
+}
+
+impl SpawnerConfig {
+    pub fn build(&self) -> Box<dyn SpawnStrategy> {
+        match *self {
+            Self::Uniform => Box::new(UniformSpawner),
+            Self::Adaptive { min_accuracy, max_accuracy } => {
+                Box::new(AdaptiveSpawner::new(min_accuracy, max_accuracy))
+            }
+        }
+    }
+}
+
+/// Picks uniformly from everything the level offers, at the level's pace.
+pub struct UniformSpawner;
+
+impl SpawnStrategy for UniformSpawner {
+    fn pick(&mut self, ctx: &SpawnContext, rng: &mut dyn RngCore) -> Option<Target> {
+        let letters = &ctx.level.letters;
+        let total = letters.len() + ctx.level.words.len();
+        if total == 0 {
+            return None;
+        }
+
+        // Sample as u32 so wasm32 and 64-bit hosts draw the same sequence
+        let idx = rng.gen_range(0..total as u32) as usize;
+        Some(if idx < letters.len() {
+            Target::Letter(letters[idx])
+        } else {
+            Target::Word(ctx.level.words[idx - letters.len()].clone())
+        })
+    }
+}
+
+/// Favors the keys the player misses or reacts slowest to, and scales the
+/// pace to hold recent accuracy inside `[min_accuracy, max_accuracy]`.
+pub struct AdaptiveSpawner {
+    pub min_accuracy: f32,
+    pub max_accuracy: f32,
+    /// Keystrokes considered when measuring recent accuracy.
+    pub window: usize,
+    /// Multiplier on the level's pace, kept within 0.5..=2.0.
+    pub difficulty: f32,
+    /// Keystrokes recorded when `difficulty` last moved; without new ones
+    /// the sample is the same and the pace holds.
+    sampled_keystrokes: usize,
+}
+
+impl AdaptiveSpawner {
+    const STEP: f32 = 0.05;
+
+    pub fn new(min_accuracy: f32, max_accuracy: f32) -> Self {
+        Self {
+            min_accuracy,
+            max_accuracy,
+            window: 20,
+            difficulty: 1.0,
+            sampled_keystrokes: 0,
+        }
+    }
+
+    /// Relative spawn weight of `key`: 1.0 for an average key, more for
+    /// keys with errors or slow reactions.
+    fn key_weight(key: char, analytics: &KeyAnalytics, mean_reaction: Option<f32>) -> f32 {
+        let Some(stats) = analytics.keys.get(&key) else {
+            return 1.0;
+        };
+        let error_weight = (1.0 - stats.accuracy()) * 3.0;
+        let reaction_weight = match (stats.average_reaction(), mean_reaction) {
+            (Some(reaction), Some(mean)) if mean > 0.0 => (reaction / mean - 1.0).max(0.0),
+            _ => 0.0,
+        };
+        1.0 + error_weight + reaction_weight
+    }
+}
+
+impl SpawnStrategy for AdaptiveSpawner {
+    fn pick(&mut self, ctx: &SpawnContext, rng: &mut dyn RngCore) -> Option<Target> {
+        let analytics = ctx.analytics;
+        let hits: u32 = analytics.keys.values().map(|k| k.hits).sum();
+        let reaction_total: f32 = analytics.keys.values().map(|k| k.reaction_total).sum();
+        let mean_reaction = (hits > 0).then(|| reaction_total / hits as f32);
+
+        let mut candidates: Vec<(Target, f32)> = ctx
+            .level
+            .letters
+            .iter()
+            .map(|&c| (Target::Letter(c), Self::key_weight(c, analytics, mean_reaction)))
+            .collect();
+        for word in &ctx.level.words {
+            let total: f32 = word.chars().map(|c| Self::key_weight(c, analytics, mean_reaction)).sum();
+            let weight = total / word.chars().count().max(1) as f32;
+            candidates.push((Target::Word(word.clone()), weight));
+        }
+
+        let total_weight: f32 = candidates.iter().map(|(_, w)| w).sum();
+        if candidates.is_empty() || total_weight <= 0.0 {
+            return None;
+        }
+
+        let mut roll = rng.gen_range(0.0..total_weight);
+        let last = candidates.len() - 1;
+        for (i, (target, weight)) in candidates.into_iter().enumerate() {
+            if roll < weight || i == last {
+                return Some(target);
+            }
+            roll -= weight;
+        }
+        None
+    }
+
+    fn pace(&mut self, ctx: &SpawnContext) -> Pace {
+        let keystrokes = ctx.stats.keystrokes().len();
+        let fresh = keystrokes != self.sampled_keystrokes;
+        if let Some(accuracy) = ctx.stats.recent_accuracy(self.window).filter(|_| fresh) {
+            self.sampled_keystrokes = keystrokes;
+            if accuracy < self.min_accuracy {
+                self.difficulty -= Self::STEP;
+            } else if accuracy > self.max_accuracy {
+                self.difficulty += Self::STEP;
+            }
+            self.difficulty = self.difficulty.clamp(0.5, 2.0);
+        }
+
+        Pace {
+            fall_speed: ctx.level.fall_speed * self.difficulty,
+            spawn_rate: ctx.level.spawn_rate / self.difficulty,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::levels::LevelPack;
+
+    #[test]
+    fn adaptive_pace_only_moves_on_new_keystrokes() {
+        let level = LevelPack::builtin().get_level(0);
+        let analytics = KeyAnalytics::new();
+        let mut stats = TypingStats::new();
+        stats.record(0.5, true);
+        let mut spawner = AdaptiveSpawner::new(0.9, 0.95);
+
+        let ctx = SpawnContext { level: &level, analytics: &analytics, stats: &stats };
+        let first = spawner.pace(&ctx);
+        assert!(first.fall_speed > level.fall_speed);
+        assert_eq!(spawner.pace(&ctx), first);
+
+        stats.record(1.0, true);
+        let ctx = SpawnContext { level: &level, analytics: &analytics, stats: &stats };
+        assert!(spawner.pace(&ctx).fall_speed > first.fall_speed);
+    }
+}
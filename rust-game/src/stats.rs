@@ -0,0 +1,206 @@
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+/// Characters per word in the standard WPM definition.
+const CHARS_PER_WORD: f32 = 5.0;
+
+/// A single counted keystroke, timestamped in seconds since the level began.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Keystroke {
+    pub time: f32,
+    pub correct: bool,
+}
+
+/// Speed and rhythm metrics derived from a run's timestamped keystrokes.
+#[derive(Clone, Debug, Default)]
+pub struct TypingStats {
+    keystrokes: Vec<Keystroke>,
+}
+
+impl TypingStats {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn record(&mut self, time: f32, correct: bool) {
+        self.keystrokes.push(Keystroke { time, correct });
+    }
+
+    pub fn keystrokes(&self) -> &[Keystroke] {
+        &self.keystrokes
+    }
+
+    /// Share of the last `window` keystrokes that were correct, from 0.0 to
+    /// 1.0, or `None` before any keystroke.
+    pub fn recent_accuracy(&self, window: usize) -> Option<f32> {
+        let start = self.keystrokes.len().saturating_sub(window);
+        let recent = &self.keystrokes[start..];
+        if recent.is_empty() {
+            return None;
+        }
+        let correct = recent.iter().filter(|k| k.correct).count();
+        Some(correct as f32 / recent.len() as f32)
+    }
+
+    /// Words per minute counting only correct keystrokes.
+    pub fn net_wpm(&self, elapsed: f32) -> f32 {
+        let correct = self.keystrokes.iter().filter(|k| k.correct).count();
+        per_minute(correct as f32 / CHARS_PER_WORD, elapsed)
+    }
+
+    /// Words per minute counting every keystroke, right or wrong.
+    pub fn raw_wpm(&self, elapsed: f32) -> f32 {
+        per_minute(self.keystrokes.len() as f32 / CHARS_PER_WORD, elapsed)
+    }
+
+    pub fn keystrokes_per_minute(&self, elapsed: f32) -> f32 {
+        per_minute(self.keystrokes.len() as f32, elapsed)
+    }
+
+    /// Standard deviation of the gaps between keystrokes in milliseconds;
+    /// lower means a steadier rhythm. `None` until there are two gaps.
+    pub fn consistency(&self) -> Option<f32> {
+        let intervals: Vec<f32> = self
+            .keystrokes
+            .windows(2)
+            .map(|pair| (pair[1].time - pair[0].time) * 1000.0)
+            .collect();
+        if intervals.len() < 2 {
+            return None;
+        }
+
+        let mean = intervals.iter().sum::<f32>() / intervals.len() as f32;
+        let variance = intervals.iter().map(|i| (i - mean).powi(2)).sum::<f32>()
+            / intervals.len() as f32;
+        Some(variance.sqrt())
+    }
+}
+
+fn per_minute(amount: f32, elapsed: f32) -> f32 {
+    if elapsed > 0.0 {
+        amount / (elapsed / 60.0)
+    } else {
+        0.0
+    }
+}
+
+/// Outcome counts and reaction time for one key.
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct KeyStats {
+    /// Targets destroyed with this key.
+    pub hits: u32,
+    /// Targets showing this key that reached the ground.
+    pub misses: u32,
+    /// Times another key was pressed while this one was expected.
+    pub mistypes: u32,
+    /// Sum of seconds from spawn (or the previous character of a word) to hit.
+    pub reaction_total: f32,
+}
+
+impl KeyStats {
+    /// Mean reaction time in seconds, if the key was ever hit.
+    pub fn average_reaction(&self) -> Option<f32> {
+        if self.hits > 0 {
+            Some(self.reaction_total / self.hits as f32)
+        } else {
+            None
+        }
+    }
+
+    /// Share of attempts at this key that were hits, from 0.0 to 1.0.
+    pub fn accuracy(&self) -> f32 {
+        let attempts = self.hits + self.misses + self.mistypes;
+        if attempts > 0 {
+            self.hits as f32 / attempts as f32
+        } else {
+            1.0
+        }
+    }
+
+    pub fn merge(&mut self, other: &KeyStats) {
+        self.hits += other.hits;
+        self.misses += other.misses;
+        self.mistypes += other.mistypes;
+        self.reaction_total += other.reaction_total;
+    }
+}
+
+/// Per-key records plus a confusion matrix of which key was pressed when
+/// another was expected.
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct KeyAnalytics {
+    pub keys: BTreeMap<char, KeyStats>,
+    /// Expected key, then the key actually pressed, then how often.
+    pub confusion: BTreeMap<char, BTreeMap<char, u32>>,
+}
+
+impl KeyAnalytics {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn record_hit(&mut self, key: char, reaction: f32) {
+        let stats = self.keys.entry(key).or_default();
+        stats.hits += 1;
+        stats.reaction_total += reaction;
+    }
+
+    pub fn record_miss(&mut self, key: char) {
+        self.keys.entry(key).or_default().misses += 1;
+    }
+
+    pub fn record_mistype(&mut self, expected: char, typed: char) {
+        self.keys.entry(expected).or_default().mistypes += 1;
+        *self.confusion.entry(expected).or_default().entry(typed).or_insert(0) += 1;
+    }
+
+    /// Keys ordered by mean reaction time, slowest first.
+    pub fn slowest(&self, count: usize) -> Vec<(char, f32)> {
+        let mut keys: Vec<(char, f32)> = self
+            .keys
+            .iter()
+            .filter_map(|(&key, stats)| stats.average_reaction().map(|r| (key, r)))
+            .collect();
+        keys.sort_by(|a, b| b.1.total_cmp(&a.1));
+        keys.truncate(count);
+        keys
+    }
+
+    /// Keys with at least one error ordered by accuracy, worst first.
+    pub fn least_accurate(&self, count: usize) -> Vec<(char, f32)> {
+        let mut keys: Vec<(char, f32)> = self
+            .keys
+            .iter()
+            .filter(|(_, stats)| stats.misses + stats.mistypes > 0)
+            .map(|(&key, stats)| (key, stats.accuracy()))
+            .collect();
+        keys.sort_by(|a, b| a.1.total_cmp(&b.1));
+        keys.truncate(count);
+        keys
+    }
+
+    /// Most frequent (expected, typed) confusions.
+    pub fn top_confusions(&self, count: usize) -> Vec<(char, char, u32)> {
+        let mut pairs: Vec<(char, char, u32)> = self
+            .confusion
+            .iter()
+            .flat_map(|(&expected, typed)| typed.iter().map(move |(&t, &n)| (expected, t, n)))
+            .collect();
+        pairs.sort_by_key(|&(_, _, n)| std::cmp::Reverse(n));
+        pairs.truncate(count);
+        pairs
+    }
+
+    /// Folds another run's records into this one.
+    pub fn merge(&mut self, other: &KeyAnalytics) {
+        for (key, stats) in &other.keys {
+            self.keys.entry(*key).or_default().merge(stats);
+        }
+        for (expected, typed) in &other.confusion {
+            let row = self.confusion.entry(*expected).or_default();
+            for (key, count) in typed {
+                *row.entry(*key).or_insert(0) += count;
+            }
+        }
+    }
+}
@@ -0,0 +1,27 @@
+// miniquad plugin telling the Rust game when the page is hidden or loses
+// focus, so a run pauses even if frames keep arriving.
+
+let lostFocus = false;
+
+export const focusPlugin = {
+  register_plugin: (importObject: any) => {
+    // Reports, and clears, whether focus was lost since the last call
+    importObject.env.typestrike_focus_lost = (): number => {
+      const lost = lostFocus || document.hidden;
+      lostFocus = false;
+      return lost ? 1 : 0;
+    };
+  },
+  on_init: () => {
+    window.addEventListener('blur', () => {
+      lostFocus = true;
+    });
+    document.addEventListener('visibilitychange', () => {
+      if (document.hidden) {
+        lostFocus = true;
+      }
+    });
+  },
+  version: '0.1.0',
+  name: 'typestrike_focus',
+};
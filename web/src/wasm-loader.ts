@@ -1,5 +1,17 @@
 // WASM Module Loader for macroquad
 import { GameFallback } from './game-fallback';
+import { focusPlugin } from './wasm-focus';
+import { storagePlugin } from './wasm-storage';
+
+/**
+ * Registers the plugins providing the Rust game's own wasm imports
+ * (`typestrike_storage_*`, `typestrike_focus_lost`). The module will not
+ * instantiate without them, so call this before loading it.
+ */
+export function registerGamePlugins(): void {
+  window.miniquad_add_plugin(storagePlugin);
+  window.miniquad_add_plugin(focusPlugin);
+}
 
 export async function initWasm(canvas: HTMLCanvasElement): Promise<void> {
   // For now, always use JavaScript fallback
@@ -25,6 +37,9 @@ export async function initWasm(canvas: HTMLCanvasElement): Promise<void> {
           throw new Error('macroquad loader not available');
         }
 
+        // Profile saves go to localStorage; focus loss pauses the game
+        registerGamePlugins();
+
         window.miniquad_add_plugin({
           register_plugin: (importObject: any) => {
             // Plugin registration (if needed)
@@ -0,0 +1,56 @@
+// miniquad plugin giving the Rust game's profile access to localStorage.
+// Strings cross the boundary as UTF-8 bytes in the wasm linear memory.
+// Required: the wasm build imports these functions, so it will not
+// instantiate unless `registerGamePlugins()` in wasm-loader.ts has run.
+
+const PROFILE_KEY = 'typestrike-profile';
+const BACKUP_KEY = 'typestrike-profile.bak';
+
+declare const wasm_memory: WebAssembly.Memory;
+
+let pending: Uint8Array | null = null;
+
+export const storagePlugin = {
+  register_plugin: (importObject: any) => {
+    importObject.env.typestrike_storage_len = (): number => {
+      const json = localStorage.getItem(PROFILE_KEY);
+      pending = json === null ? null : new TextEncoder().encode(json);
+      return pending ? pending.length : 0;
+    };
+
+    importObject.env.typestrike_storage_read = (ptr: number, len: number) => {
+      if (pending) {
+        new Uint8Array(wasm_memory.buffer, ptr, len).set(pending.subarray(0, len));
+        pending = null;
+      }
+    };
+
+    // setItem throws when storage is full or disabled; 0 reports that
+    importObject.env.typestrike_storage_write = (ptr: number, len: number): number => {
+      const bytes = new Uint8Array(wasm_memory.buffer, ptr, len);
+      try {
+        localStorage.setItem(PROFILE_KEY, new TextDecoder().decode(bytes));
+        return 1;
+      } catch {
+        return 0;
+      }
+    };
+
+    // Keeps an unreadable save before the game starts a fresh one
+    importObject.env.typestrike_storage_backup = (): number => {
+      const json = localStorage.getItem(PROFILE_KEY);
+      if (json === null) {
+        return 1;
+      }
+      try {
+        localStorage.setItem(BACKUP_KEY, json);
+        return 1;
+      } catch {
+        return 0;
+      }
+    };
+  },
+  on_init: () => {},
+  version: '0.1.0',
+  name: 'typestrike_storage',
+};